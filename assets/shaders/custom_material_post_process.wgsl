@@ -24,12 +24,31 @@ struct Ripple {
 	start_time: f32,
 	start_x: f32,
 	start_y: f32,
+	strength: f32,
+};
+
+// RIPPLE_COUNT in src/vfx/mod.rs
+const RIPPLE_COUNT: i32 = 8;
+
+struct Ripples {
+	ripples: array<Ripple, RIPPLE_COUNT>,
 	velocity: f32,
+	padding_a: f32,
+	padding_b: f32,
+	padding_c: f32,
+};
+
+struct Safety {
+	vignette: f32,
+	desaturate: f32,
+	padding_a: f32,
+	padding_b: f32,
 };
 
 struct ShaderSettings {
 	strength: Strength,
-	ripple: Ripple
+	ripples: Ripples,
+	safety: Safety
 };
 
 struct Time {
@@ -52,7 +71,9 @@ fn fragment(
 	let offset_strength = settings.strength.wave_distortion;
 	uv += 0.07 * offset_strength * wave_defect(0.0, 2.70 * uv.xy);
 	// Compute an offset vector using wave_defect()
-	uv -= ripple_defect(uv.xy);
+	for (var i = 0; i < RIPPLE_COUNT; i++) {
+		uv -= ripple_defect(settings.ripples.ripples[i], uv.xy);
+	}
 	uv += 0.05 * offset_strength * wave_defect(37., 16.3 * (uv.xy - uv_0)); // Higher frequency
 	uv += 0.07 * offset_strength * wave_defect(11., 3.18 * (uv.xy - 0.9 * uv_0));
 	uv += 0.25 * offset_strength * wave_defect(80., 0.37 * (uv.xy - uv_0)); // Low frequency, large waves
@@ -85,6 +106,12 @@ fn fragment(
 	// Convert back to rgb
 	rgb = hsv_to_rgb(hsv);
 
+	// Safe mode stand-ins, washed out colours and darkened edges instead of heavy distortion
+	let grey = dot(luma_map, rgb);
+	rgb = mix(rgb, vec3<f32>(grey), settings.safety.desaturate);
+	let edge_dist = length(uv_0 - vec2<f32>(0.5, 0.5));
+	rgb *= 1.0 - settings.safety.vignette * smoothstep(0.3, 0.8, edge_dist);
+
 	// Tone-map
 	let luminance_b = dot(luma_map, rgb * rgb);
 	rgb /= (1.0 + luminance_b);
@@ -97,12 +124,16 @@ fn boost_contrast(amount: f32, value: vec3<f32>) -> vec3<f32> {
 	return fixed_value + (1.0 + amount) * rel_value;
 }
 
-fn ripple_defect(uv: vec2<f32>) -> vec2<f32> {
-	let rel_uv = uv - vec2<f32>(settings.ripple.start_x, settings.ripple.start_y);
-	let ripple_radius = settings.ripple.velocity * (clock.value - settings.ripple.start_time);
+fn ripple_defect(ripple: Ripple, uv: vec2<f32>) -> vec2<f32> {
+	let ripple_radius = settings.ripples.velocity * (clock.value - ripple.start_time);
+	// Unused slots and ripples long gone off screen
+	if (ripple.strength <= 0.0 || ripple_radius > 2.0) {
+		return vec2<f32>(0.0);
+	}
+	let rel_uv = uv - vec2<f32>(ripple.start_x, ripple.start_y);
 	let dist = length(rel_uv) - ripple_radius;
 	let width = settings.strength.ripple_distortion;
-	let gaub = 0.1 * exp(-(dist * dist) / (width * width));// / width; // Commented out because they'd cancel anyway
+	let gaub = 0.1 * ripple.strength * exp(-(dist * dist) / (width * width));// / width; // Commented out because they'd cancel anyway
 	return normalize(rel_uv) * gaub;// * settings.strength.ripple_distortion;
 }
 
@@ -0,0 +1,90 @@
+#import bevy_sprite::mesh2d_view_bindings
+#import bevy_pbr::utils
+
+@group(1) @binding(0)
+var texture: texture_2d<f32>;
+
+@group(1) @binding(1)
+var our_sampler: sampler;
+
+@group(1) @binding(2)
+var<uniform> settings: PassSettings;
+
+// Kind numbers match EffectKind::shader_id
+struct PassSettings {
+	kind: u32,
+	amount: f32,
+	scale: f32,
+	time: f32,
+};
+
+@fragment
+fn fragment(
+	@builtin(position) position: vec4<f32>,
+	#import bevy_sprite::mesh2d_vertex_output
+) -> @location(0) vec4<f32> {
+	// Get screen position with coordinates from 0 to 1
+	let uv = coords_to_viewport_uv(position.xy, view.viewport);
+	let texel = 1.0 / vec2<f32>(textureDimensions(texture));
+
+	let source = textureSample(texture, our_sampler, uv).rgb;
+	var rgb = source;
+	switch settings.kind {
+		case 0u: {
+			rgb = crt(uv, source);
+		}
+		case 1u: {
+			rgb = vignette(uv, source);
+		}
+		case 2u: {
+			rgb = chromatic_aberration(uv, texel, source);
+		}
+		case 3u: {
+			rgb = blur(uv, texel);
+		}
+		case 4u: {
+			rgb = pixelate(uv, texel);
+		}
+		default: {}
+	}
+	return vec4<f32>(mix(source, rgb, settings.amount), 1.0);
+}
+
+fn crt(uv: vec2<f32>, source: vec3<f32>) -> vec3<f32> {
+	// Dark bands between the lines, slowly rolling down the screen
+	let line = 0.5 + 0.5 * cos(6.2831853 * (uv.y * settings.scale - 0.5 * settings.time));
+	// Brightened a little so the screen doesn't get darker overall
+	return source * (0.6 + 0.4 * line) * 1.2;
+}
+
+fn vignette(uv: vec2<f32>, source: vec3<f32>) -> vec3<f32> {
+	let edge_dist = length(uv - vec2<f32>(0.5, 0.5));
+	return source * (1.0 - smoothstep(settings.scale, settings.scale + 0.45, edge_dist));
+}
+
+fn chromatic_aberration(uv: vec2<f32>, texel: vec2<f32>, source: vec3<f32>) -> vec3<f32> {
+	// No split in the middle, the full split in the corners
+	let offset = (uv - vec2<f32>(0.5, 0.5)) * 1.4 * settings.scale * texel;
+	let red = textureSample(texture, our_sampler, uv + offset).r;
+	let blue = textureSample(texture, our_sampler, uv - offset).b;
+	return vec3<f32>(red, source.g, blue);
+}
+
+fn blur(uv: vec2<f32>, texel: vec2<f32>) -> vec3<f32> {
+	// 3x3 gaussian, spread out to the radius
+	let step = settings.scale * texel;
+	var total = vec3<f32>(0.0);
+	for (var x = -1; x <= 1; x++) {
+		for (var y = -1; y <= 1; y++) {
+			let weight = f32((2 - abs(x)) * (2 - abs(y)));
+			total += weight * textureSample(texture, our_sampler, uv + step * vec2<f32>(f32(x), f32(y))).rgb;
+		}
+	}
+	return total / 16.0;
+}
+
+fn pixelate(uv: vec2<f32>, texel: vec2<f32>) -> vec3<f32> {
+	let block = max(settings.scale, 1.0) * texel;
+	let centre = (floor(uv / block) + 0.5) * block;
+	return textureSample(texture, our_sampler, centre).rgb;
+}
@@ -0,0 +1,126 @@
+// Audio is split over one channel per bus, each with its own volume and mute from the settings
+// Music ducks under the voice while a cutscene is talking
+
+use bevy::prelude::*;
+use bevy_kira_audio::prelude::*;
+use serde::{Serialize, Deserialize};
+
+use crate::{menu::{MenuState, options::OptionsState}, settings::Settings};
+
+// How far the music drops while the voice plays, and how many seconds it takes to get there and back
+const DUCKED_MUSIC: f64 = 0.3;
+const DUCK_TIME: f64 = 0.5;
+
+pub struct ChannelsPlugin;
+
+impl Plugin for ChannelsPlugin {
+	fn build(&self, app: &mut App) {
+		app
+		.add_audio_channel::<MusicChannel>()
+		.add_audio_channel::<SfxChannel>()
+		.add_audio_channel::<VoiceChannel>()
+		.insert_resource(Ducking(1.0))
+		// Typing names and rebinding keys shouldn't change the volume
+		.add_system(audio_volume_control
+			.run_if(not(in_state(MenuState::NameEntry)))
+			.run_if(in_state(OptionsState::Closed)))
+		.add_system(duck_music)
+		.add_system(apply_volume_settings
+			.after(duck_music)
+			.run_if(resource_changed::<Settings>().or_else(resource_changed::<Ducking>())))
+		;
+	}
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum AudioBus {
+	Music,
+	Sfx,
+	Voice,
+}
+
+impl AudioBus {
+	pub fn name(&self) -> &'static str {
+		match self {
+			AudioBus::Music => "Music",
+			AudioBus::Sfx => "Sound Effects",
+			AudioBus::Voice => "Voice",
+		}
+	}
+}
+
+// Menu and fever music, minigame music plays on its own stems but follows the same volume
+#[derive(Resource)]
+pub struct MusicChannel;
+
+#[derive(Resource)]
+pub struct SfxChannel;
+
+// Cutscene narration
+#[derive(Resource)]
+pub struct VoiceChannel;
+
+// Multiplier on the music volume, 1 when nothing is talking over it
+#[derive(Resource)]
+pub struct Ducking(pub f64);
+
+impl Ducking {
+	pub fn music_volume(&self, settings: &Settings) -> f64 {
+		settings.audible_volume(AudioBus::Music) * self.0
+	}
+}
+
+fn audio_volume_control(
+	keyboard: Res<Input<KeyCode>>,
+	mut settings: ResMut<Settings>,
+) {
+	let volume_keys = [
+		KeyCode::Key0, KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4,
+		KeyCode::Key5, KeyCode::Key6, KeyCode::Key7, KeyCode::Key8, KeyCode::Key9,
+	];
+	for (i, key) in volume_keys.iter().enumerate() {
+		if keyboard.just_pressed(*key) {
+			for bus in [AudioBus::Music, AudioBus::Sfx, AudioBus::Voice] {
+				settings.set_volume(bus, (i + 1) as f64 / 10.0);
+			}
+			settings.muted = false;
+		}
+	}
+	let bindings = settings.key_bindings.clone();
+	if keyboard.just_pressed(bindings.mute) {
+		settings.muted = !settings.muted;
+	}
+	if keyboard.just_pressed(bindings.mute_music) {
+		settings.toggle_bus_mute(AudioBus::Music);
+	}
+	if keyboard.just_pressed(bindings.mute_sfx) {
+		settings.toggle_bus_mute(AudioBus::Sfx);
+	}
+}
+
+fn duck_music(
+	voice: Res<AudioChannel<VoiceChannel>>,
+	mut ducking: ResMut<Ducking>,
+	time: Res<Time>,
+) {
+	let target = if voice.is_playing_sound() {DUCKED_MUSIC} else {1.0};
+	let step = (1.0 - DUCKED_MUSIC) * time.delta_seconds_f64() / DUCK_TIME;
+	let level = if ducking.0 < target {(ducking.0 + step).min(target)} else {(ducking.0 - step).max(target)};
+	// Only marks the resource changed while it's moving
+	if level != ducking.0 {
+		ducking.0 = level;
+	}
+}
+
+// Channel volume carries over to sounds started later
+fn apply_volume_settings(
+	music: Res<AudioChannel<MusicChannel>>,
+	sfx: Res<AudioChannel<SfxChannel>>,
+	voice: Res<AudioChannel<VoiceChannel>>,
+	settings: Res<Settings>,
+	ducking: Res<Ducking>,
+) {
+	music.set_volume(ducking.music_volume(&settings));
+	sfx.set_volume(settings.audible_volume(AudioBus::Sfx));
+	voice.set_volume(settings.audible_volume(AudioBus::Voice));
+}
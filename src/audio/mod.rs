@@ -1,2 +1,3 @@
+pub mod channels;
 pub mod music;
 pub mod sfx;
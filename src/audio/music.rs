@@ -1,144 +1,260 @@
+// Music is picked from the MusicTable, the first cue matching the current states and stats plays
+// Every change of track goes through here, so tracks never stack or cut each other off
+
 use std::time::Duration;
 
-use bevy::prelude::*;
+use bevy::{prelude::*, ecs::system::SystemParam};
 use bevy_kira_audio::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+	game::{states::{GameState, DayState}, minigames::{Minigames, fever::Fever}},
+	setup::UserData,
+	settings::Settings,
+};
 
-use crate::{game::states::{GameState, DayState}, setup::UserData};
+use super::channels::{Ducking, MusicChannel};
 
 pub struct MusicPlugin;
 
 impl Plugin for MusicPlugin {
     fn build(&self, app: &mut App) {
         app
-		.add_system(audio_volume_control)
-		.add_system(play_menu_music.in_schedule(OnEnter(GameState::Menu)))
-		.add_system(stop_music.in_schedule(OnExit(GameState::Menu)))
-		.add_system(play_morning_music.in_schedule(OnEnter(DayState::Morning)))
-		.add_system(stop_music.in_schedule(OnExit(DayState::Morning)))
-		.add_system(play_evening_music.in_schedule(OnEnter(DayState::Evening)))
-		.add_system(stop_music.in_schedule(OnExit(DayState::Evening)))
-		.add_system(play_night_music.in_schedule(OnEnter(DayState::Night)))
-		.add_system(stop_music.in_schedule(OnExit(DayState::Night)))
+		.add_audio_channel::<SoberMusic>()
+		.add_audio_channel::<HighMusic>()
+		.init_resource::<MusicTable>()
+		.init_resource::<NowPlaying>()
+		.add_system(choose_music)
+		.add_system(mix_music_stems)
 		;
 	}
 }
 
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub enum MusicTrack {
+	Single(String),
+	// Sober and high versions of the same track, crossfaded with royal
+	Stems(String, String),
+	// Stems of whichever minigame the day state belongs to
+	Minigame,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+pub enum MusicCondition {
+	#[default]
+	Always,
+	Fever,
+	DrugsTaken(f32),
+}
+
+impl MusicCondition {
+	fn holds(&self, user_data: &UserData, fever: &Fever) -> bool {
+		match self {
+			MusicCondition::Always => true,
+			MusicCondition::Fever => fever.active(),
+			MusicCondition::DrugsTaken(drugs) => user_data.drugs_taken >= *drugs,
+		}
+	}
+}
+
+fn default_fade() -> f32 {
+	2.0
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct MusicCue {
+	pub game_state: GameState,
+	// Any day state when left out
+	#[serde(default)]
+	pub day_state: Option<DayState>,
+	#[serde(default)]
+	pub condition: MusicCondition,
+	pub track: MusicTrack,
+	// Seconds to fade in when the cue starts and out when another takes over
+	#[serde(default = "default_fade")]
+	pub fade_in: f32,
+	#[serde(default = "default_fade")]
+	pub fade_out: f32,
+}
+
+// Checked top to bottom, states with no matching cue are silent
+#[derive(Resource, Debug)]
+pub struct MusicTable(pub Vec<MusicCue>);
+
+impl MusicTable {
+	pub fn parse(source: &str) -> Result<Self, ron::error::SpannedError> {
+		ron::from_str(source).map(MusicTable)
+	}
+}
+
+// Baked in like droppables.ron
+impl Default for MusicTable {
+	fn default() -> Self {
+		MusicTable::parse(include_str!("../../assets/data/music.ron"))
+			.expect("assets/data/music.ron is malformed")
+	}
+}
+
+// Stems as file paths, once Minigame has been looked up
+#[derive(Clone, PartialEq, Debug)]
+enum Playing {
+	Single(String),
+	Stems(String, String),
+}
+
+#[derive(Resource, Default)]
+pub struct NowPlaying {
+	playing: Option<Playing>,
+	fade_out: f32,
+}
+
+// Minigame music comes in two stems, sober and high, which play in sync on their own channels
+// and crossfade as royal changes
 #[derive(Resource)]
-pub struct Volume(pub f64);
+pub struct SoberMusic;
 
+#[derive(Resource)]
+pub struct HighMusic;
 
-// Create generic "play music" function which checks next scene during transistion and loads
-// the correct audio file
-pub fn play_menu_music(
-	audio: Res<Audio>,
-	asset_server: Res<AssetServer>,
-) {
-	audio.play(asset_server.load("audio/bgm/menu_music.ogg"))
-	.fade_in(AudioTween::linear(Duration::new(2, 0)))
-	.looped();
+// Royal, out of 1 plus the unstable royal, over which the music goes from fully sober to fully high
+const HIGH_MIX_START: f32 = 0.3;
+const HIGH_MIX_END: f32 = 0.7;
+
+#[derive(SystemParam)]
+pub struct MusicStems<'w> {
+	sober: Res<'w, AudioChannel<SoberMusic>>,
+	high: Res<'w, AudioChannel<HighMusic>>,
 }
 
-pub fn play_morning_music(
-	audio: Res<Audio>,
-	asset_server: Res<AssetServer>,
-	user_data: Res<UserData>,
-) {
-	if user_data.royal > 50.0 {
-		audio.play(asset_server.load("audio/bgm/deep_fried_sunset.ogg"))
-		.fade_in(AudioTween::linear(Duration::new(2, 0)))
+impl<'w> MusicStems<'w> {
+	// Both stems start on the same frame so they stay in sync
+	pub fn play(&self, asset_server: &AssetServer, sober: &str, high: &str, fade: Duration) {
+		self.sober.play(asset_server.load(sober))
+		.fade_in(AudioTween::linear(fade))
 		.looped();
-	} else {
-		audio.play(asset_server.load("audio/bgm/jpeg_of_a_sunset.ogg"))
-		.fade_in(AudioTween::linear(Duration::new(2, 0)))
+		self.high.play(asset_server.load(high))
+		.fade_in(AudioTween::linear(fade))
 		.looped();
 	}
+
+	pub fn stop(&self, fade: Duration) {
+		self.sober.stop().fade_out(AudioTween::linear(fade));
+		self.high.stop().fade_out(AudioTween::linear(fade));
+	}
 }
 
-pub fn play_evening_music(
-	audio: Res<Audio>,
-	asset_server: Res<AssetServer>,
-	user_data: Res<UserData>,
-) {
-	if user_data.royal > 50.0 {
-		audio.play(asset_server.load("audio/bgm/Mars_Attaque.ogg"))
-		.fade_in(AudioTween::linear(Duration::new(2, 0)))
-		.looped();
-	} else {
-		audio.play(asset_server.load("audio/bgm/Le_Pique_Nique_Martien.ogg"))
-		.fade_in(AudioTween::linear(Duration::new(2, 0)))
-		.looped();
+#[derive(SystemParam)]
+pub struct MusicPlayer<'w> {
+	music: Res<'w, AudioChannel<MusicChannel>>,
+	stems: MusicStems<'w>,
+	asset_server: Res<'w, AssetServer>,
+	now_playing: ResMut<'w, NowPlaying>,
+}
+
+impl<'w> MusicPlayer<'w> {
+	// Fades out whatever is playing while the new track fades in
+	fn switch(&mut self, next: Option<Playing>, fade_in: f32, fade_out: f32) {
+		if self.now_playing.playing == next {
+			return;
+		}
+		let fade = Duration::from_secs_f32(self.now_playing.fade_out);
+		match &self.now_playing.playing {
+			Some(Playing::Single(_)) => {self.music.stop().fade_out(AudioTween::linear(fade));},
+			Some(Playing::Stems(..)) => self.stems.stop(fade),
+			None => (),
+		}
+		let fade = Duration::from_secs_f32(fade_in);
+		match &next {
+			Some(Playing::Single(track)) => {
+				self.music.play(self.asset_server.load(track.as_str()))
+				.fade_in(AudioTween::linear(fade))
+				.looped();
+			},
+			Some(Playing::Stems(sober, high)) => self.stems.play(&self.asset_server, sober, high, fade),
+			None => (),
+		}
+		self.now_playing.playing = next;
+		self.now_playing.fade_out = fade_out;
 	}
 }
 
-pub fn play_night_music(
-	audio: Res<Audio>,
-	asset_server: Res<AssetServer>,
+// How far into the high stem the music is, from 0 to 1
+pub fn high_mix(
+	user_data: &UserData,
+) -> f64 {
+	let royal = user_data.royal / 100.0 + user_data.unstable_royal;
+	((royal - HIGH_MIX_START) / (HIGH_MIX_END - HIGH_MIX_START)).clamp(0.0, 1.0) as f64
+}
+
+fn choose_music(
+	table: Res<MusicTable>,
+	game_state: Res<State<GameState>>,
+	day_state: Res<State<DayState>>,
+	minigames: Res<Minigames>,
+	fever: Res<Fever>,
 	user_data: Res<UserData>,
+	mut player: MusicPlayer,
 ) {
-	if user_data.royal > 50.0 {
-		audio.play(asset_server.load("audio/bgm/parlour_sugar_rush.ogg"))
-		.fade_in(AudioTween::linear(Duration::new(2, 0)))
-		.looped();
-	} else {
-		audio.play(asset_server.load("audio/bgm/parlour_sober_final.ogg"))
-		.fade_in(AudioTween::linear(Duration::new(2, 0)))
-		.looped();
+	let cue = table.0.iter().find_map(|cue| {
+		if cue.game_state != game_state.0
+		|| cue.day_state.is_some_and(|wanted| wanted != day_state.0)
+		|| !cue.condition.holds(&user_data, &fever) {
+			return None;
+		}
+		let playing = match &cue.track {
+			MusicTrack::Single(track) => Playing::Single(track.clone()),
+			MusicTrack::Stems(sober, high) => Playing::Stems(sober.clone(), high.clone()),
+			MusicTrack::Minigame => {
+				let minigame = minigames.get(day_state.0)?;
+				Playing::Stems(minigame.music.to_string(), minigame.high_music.to_string())
+			},
+		};
+		Some((playing, cue))
+	});
+	match cue {
+		Some((playing, cue)) => player.switch(Some(playing), cue.fade_in, cue.fade_out),
+		None => player.switch(None, 0.0, 0.0),
 	}
 }
 
-pub fn stop_music(
-	audio: Res<Audio>,
+fn mix_music_stems(
+	stems: MusicStems,
+	user_data: Res<UserData>,
+	settings: Res<Settings>,
+	ducking: Res<Ducking>,
+	mut last_mix: Local<Option<(f64, f64)>>,
 ) {
-	audio.stop().fade_out(AudioTween::linear(Duration::new(2,0)));
+	let volume = ducking.music_volume(&settings);
+	let mix = high_mix(&user_data);
+	// Only tell the channels when something audible changed
+	if let Some((last_volume, last)) = *last_mix {
+		if last_volume == volume && (last - mix).abs() < 0.01 {
+			return;
+		}
+	}
+	*last_mix = Some((volume, mix));
+	stems.sober.set_volume(volume * (1.0 - mix));
+	stems.high.set_volume(volume * mix);
 }
 
-pub fn audio_volume_control(
-	keyboard: Res<Input<KeyCode>>,
-	audio: Res<Audio>,
-	mut volume: ResMut<Volume>,
-) {
-	if keyboard.just_pressed(KeyCode::Key0) {
-		audio.set_volume(0.1);
-		volume.0 = 0.0;
-	}
-	if keyboard.just_pressed(KeyCode::Key1) {
-		audio.set_volume(0.2);
-		volume.0 = 0.1;
-	}
-	if keyboard.just_pressed(KeyCode::Key2) {
-		audio.set_volume(0.3);
-		volume.0 = 0.2;
-	}
-	if keyboard.just_pressed(KeyCode::Key3) {
-		audio.set_volume(0.4);
-		volume.0 = 0.3;
-	}
-	if keyboard.just_pressed(KeyCode::Key4) {
-		audio.set_volume(0.5);
-		volume.0 = 0.4;
-	}
-	if keyboard.just_pressed(KeyCode::Key5) {
-		audio.set_volume(0.6);
-		volume.0 = 0.5;
-	}
-	if keyboard.just_pressed(KeyCode::Key6) {
-		audio.set_volume(0.7);
-		volume.0 = 0.6;
-	}
-	if keyboard.just_pressed(KeyCode::Key7) {
-		audio.set_volume(0.8);
-		volume.0 = 0.7;
-	}
-	if keyboard.just_pressed(KeyCode::Key8) {
-		audio.set_volume(0.9);
-		volume.0 = 0.8;
-	}
-	if keyboard.just_pressed(KeyCode::Key9) {
-		audio.set_volume(1.0);
-		volume.0 = 0.9;
-	}
-	if keyboard.just_pressed(KeyCode::M) {
-		audio.set_volume(0.0);
-		volume.0 = 1.0;
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn every_cue_has_its_music() {
+		let table = MusicTable::default();
+		assert!(!table.0.is_empty(), "music.ron has no cues");
+		for cue in table.0.iter() {
+			let files = match &cue.track {
+				MusicTrack::Single(track) => vec![track],
+				MusicTrack::Stems(sober, high) => vec![sober, high],
+				MusicTrack::Minigame => vec![],
+			};
+			for file in files {
+				let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("assets").join(file);
+				assert!(path.exists(), "{:?} points at missing {}", cue.game_state, file);
+			}
+		}
 	}
-}
\ No newline at end of file
+}
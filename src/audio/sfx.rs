@@ -1,65 +1,313 @@
+// Peg hits, panned to where on the board they happened and pitched by how hard they were
+// Only a few hits sound at once, a new one takes over the least important voice if it matters more
+// Everything else sends a PlaySfx, and sfx.ron decides what it sounds like
+
+use std::collections::BTreeMap;
+
 use bevy::prelude::*;
-use bevy_kira_audio::{Audio, AudioControl};
-use rand::seq::SliceRandom;
-use crate::{game::physics::BallCollisionEvent, setup::UserData};
+use bevy_kira_audio::prelude::*;
+use rand::{Rng, seq::SliceRandom};
+use serde::Deserialize;
+
+use crate::{
+	game::{food::DropType, pegs::PegType, physics::{BallCollisionEvent, BallCollisionEventType}, states::{GameState, DayState}},
+	setup::UserData,
+	settings::Settings,
+};
 
-use super::music::Volume;
+use super::{channels::{AudioBus, SfxChannel}, music::high_mix};
+
+const MAX_VOICES: usize = 4;
+// Slower hits are balls settling against pegs and stay silent
+const MIN_HIT_SPEED: f32 = 60.0;
+// Hits this fast sound at the highest pitch
+const HARD_HIT_SPEED: f32 = 800.0;
+const BOARD_WIDTH: f32 = 1600.0;
+// How far to the sides hits are panned, 1 is fully left and right
+const PAN_WIDTH: f64 = 0.8;
+const VOICE_START_TIME: f64 = 0.1;
 
 pub struct SFXPlugin;
 
 impl Plugin for SFXPlugin {
 	fn build(&self, app: &mut App) {
 		app
-		.insert_resource(DingTimer(Timer::from_seconds(0.2, TimerMode::Once)))
-		.add_system(tick_timer)
+		.add_event::<PlaySfx>()
+		.init_resource::<SfxVoices>()
+		.init_resource::<SfxMap>()
 		.add_system(ball_collision_sound.run_if(on_event::<BallCollisionEvent>()))
+		.add_system(button_sounds)
+		.add_system(day_transition_sound
+			.run_if(state_changed::<DayState>())
+			.run_if(in_state(GameState::Game)))
+		.add_system(play_sfx.run_if(on_event::<PlaySfx>()))
 		;
 	}
 }
 
-#[derive(Resource)]
-pub struct DingTimer(pub Timer);
+// Game events with a sound of their own
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Deserialize)]
+pub enum Sfx {
+	Launch,
+	BasketHit,
+	BasketMiss,
+	Eat,
+	Soup,
+	Bell,
+	Prize,
+	Hover,
+	Click,
+	DayTransition,
+}
 
-fn tick_timer(
-	mut ding_timer: ResMut<DingTimer>,
-	time: Res<Time>,
-) {
-	ding_timer.0.tick(time.delta());
+// Panned to pos on the board when there is one, centred otherwise
+pub struct PlaySfx {
+	pub sfx: Sfx,
+	pub pos: Option<Vec2>,
+}
+
+impl PlaySfx {
+	pub fn at(sfx: Sfx, pos: Vec2) -> Self {
+		Self {sfx, pos: Some(pos)}
+	}
+}
+
+impl From<Sfx> for PlaySfx {
+	fn from(sfx: Sfx) -> Self {
+		Self {sfx, pos: None}
+	}
+}
+
+fn default_pitch() -> (f64, f64) {
+	(1.0, 1.0)
+}
+
+fn default_volume() -> f64 {
+	1.0
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct SfxCue {
+	pub sounds: Vec<String>,
+	#[serde(default = "default_volume")]
+	pub volume: f64,
+	#[serde(default = "default_pitch")]
+	pub pitch: (f64, f64),
+	#[serde(default)]
+	pub cooldown: f64,
+}
+
+#[derive(Resource, Debug)]
+pub struct SfxMap(pub BTreeMap<Sfx, SfxCue>);
+
+impl SfxMap {
+	pub fn parse(source: &str) -> Result<Self, ron::error::SpannedError> {
+		ron::from_str(source).map(SfxMap)
+	}
+}
+
+// Baked in like droppables.ron
+impl Default for SfxMap {
+	fn default() -> Self {
+		SfxMap::parse(include_str!("../../assets/data/sfx.ron"))
+			.expect("assets/data/sfx.ron is malformed")
+	}
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PegMaterial {
+	Metal,
+	// Food squelches
+	Wet,
+	Bell,
+}
+
+impl PegMaterial {
+	pub fn of(peg_type: PegType) -> Self {
+		match peg_type {
+			PegType::PachinkoPeg => PegMaterial::Metal,
+			PegType::ItemPeg(DropType::Bell) => PegMaterial::Bell,
+			PegType::ItemPeg(drop_type) if drop_type.is_edible() => PegMaterial::Wet,
+			PegType::ItemPeg(_) => PegMaterial::Metal,
+		}
+	}
+
+	fn sounds(&self, high: bool) -> &'static [&'static str] {
+		match self {
+			PegMaterial::Metal if high => &[
+				"audio/sfx/pegging.ogg", "audio/sfx/peg1.ogg", "audio/sfx/peg2.ogg", "audio/sfx/peg3.ogg", "audio/sfx/peg4.ogg",
+				"audio/sfx/peg_st1.ogg", "audio/sfx/peg_st2.ogg", "audio/sfx/peg_st3.ogg", "audio/sfx/peg_st4.ogg",
+			],
+			PegMaterial::Metal | PegMaterial::Bell => &[
+				"audio/sfx/pegging.ogg", "audio/sfx/peg1.ogg", "audio/sfx/peg2.ogg", "audio/sfx/peg3.ogg", "audio/sfx/peg4.ogg",
+			],
+			PegMaterial::Wet => &["audio/sfx/wet1.ogg", "audio/sfx/wet2.ogg", "audio/sfx/wet3.ogg"],
+		}
+	}
+
+	// Bells are the peg sounds pitched up into a ring
+	fn pitch(&self) -> f64 {
+		match self {
+			PegMaterial::Bell => 1.6,
+			_ => 1.0,
+		}
+	}
+
+	// Bells matter to the game, so they win over ordinary hits
+	fn priority(&self) -> f32 {
+		match self {
+			PegMaterial::Bell => 1.0,
+			PegMaterial::Wet => 0.2,
+			PegMaterial::Metal => 0.0,
+		}
+	}
+}
+
+struct Voice {
+	instance: Handle<AudioInstance>,
+	priority: f32,
+	// Seconds since startup, the instance only shows up once the audio thread has started it
+	started: f64,
+}
+
+#[derive(Resource, Default)]
+pub struct SfxVoices(Vec<Voice>);
+
+// Left to right from 0 to 1, like kira's panning
+pub fn board_panning(
+	x: f32,
+) -> f64 {
+	0.5 + ((x / BOARD_WIDTH).clamp(0.0, 1.0) as f64 - 0.5) * PAN_WIDTH
 }
 
 fn ball_collision_sound(
 	asset_server: Res<AssetServer>,
-	audio: Res<Audio>,
-	mut ding_timer: ResMut<DingTimer>,
+	sfx_channel: Res<AudioChannel<SfxChannel>>,
+	mut collision_events: EventReader<BallCollisionEvent>,
+	mut voices: ResMut<SfxVoices>,
+	mut audio_instances: ResMut<Assets<AudioInstance>>,
 	user_data: Res<UserData>,
-	volume: Res<Volume>,
+	time: Res<Time>,
 ) {
-	if ding_timer.0.finished() {
-		let mut sounds = vec![
-			"audio/sfx/pegging.ogg",
-			"audio/sfx/peg1.ogg",
-			"audio/sfx/peg2.ogg",
-			"audio/sfx/peg3.ogg",
-			"audio/sfx/peg4.ogg",
-		];
-		if user_data.royal > 50.0 {
-			let mut sounds_st = vec![
-				"audio/sfx/peg_st1.ogg",
-				"audio/sfx/peg_st2.ogg",
-				"audio/sfx/peg_st3.ogg",
-				"audio/sfx/peg_st4.ogg",
-			];
-
-			sounds.append(&mut sounds_st);
+	// Finished voices free up their place
+	let now = time.elapsed_seconds_f64();
+	voices.0.retain(|voice| match audio_instances.get(&voice.instance) {
+		Some(instance) => !matches!(instance.state(), PlaybackState::Stopped),
+		None => now - voice.started < VOICE_START_TIME,
+	});
+
+	let mut hits: Vec<(f32, PegMaterial, &BallCollisionEvent)> = collision_events.iter()
+		.filter(|collision| collision.speed >= MIN_HIT_SPEED)
+		.map(|collision| {
+			let material = match collision.collision_type {
+				BallCollisionEventType::Peg {peg_type, ..} => PegMaterial::of(peg_type),
+				BallCollisionEventType::Ball => PegMaterial::Metal,
+			};
+			(collision.speed / HARD_HIT_SPEED + material.priority(), material, collision)
+		})
+		.collect();
+	hits.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+	let high = high_mix(&user_data);
+	let mut rng = rand::thread_rng();
+	for (priority, material, collision) in hits.into_iter().take(MAX_VOICES) {
+		if voices.0.len() >= MAX_VOICES {
+			let Some((quietest, _)) = voices.0.iter().enumerate()
+				.min_by(|(_, a), (_, b)| a.priority.total_cmp(&b.priority)) else {
+				break;
+			};
+			if voices.0[quietest].priority >= priority {
+				continue;
+			}
+			let voice = voices.0.swap_remove(quietest);
+			if let Some(instance) = audio_instances.get_mut(&voice.instance) {
+				instance.stop(AudioTween::default());
+			}
 		}
+		let hardness = (collision.speed / HARD_HIT_SPEED).clamp(0.0, 1.0) as f64;
+		// Harder hits ring higher, royal drags everything down and a little randomness keeps repeats apart
+		let pitch = material.pitch()
+			* (0.85 + 0.3 * hardness)
+			* (1.0 - 0.25 * high)
+			* rng.gen_range(0.97..1.03);
+		let sound = *material.sounds(high > 0.5).choose(&mut rng).unwrap();
+		let instance = sfx_channel.play(asset_server.load(sound))
+			.with_panning(board_panning(collision.pos.x))
+			.with_playback_rate(pitch)
+			.handle();
+		voices.0.push(Voice {instance, priority, started: now});
+	}
+}
+
+fn play_sfx(
+	asset_server: Res<AssetServer>,
+	sfx_channel: Res<AudioChannel<SfxChannel>>,
+	mut sfx_events: EventReader<PlaySfx>,
+	map: Res<SfxMap>,
+	settings: Res<Settings>,
+	time: Res<Time>,
+	mut last_played: Local<BTreeMap<Sfx, f64>>,
+) {
+	let now = time.elapsed_seconds_f64();
+	let mut rng = rand::thread_rng();
+	for event in sfx_events.iter() {
+		let Some(cue) = map.0.get(&event.sfx) else {
+			warn!("{:?} has no sound in sfx.ron", event.sfx);
+			continue;
+		};
+		if last_played.get(&event.sfx).is_some_and(|last| now - last < cue.cooldown) {
+			continue;
+		}
+		let Some(sound) = cue.sounds.choose(&mut rng) else {
+			continue;
+		};
+		last_played.insert(event.sfx, now);
+		let (low, high) = cue.pitch;
+		let pitch = if high > low {rng.gen_range(low..high)} else {low};
+		// Volume set on a sound replaces the channel's, so the settings are folded in here
+		sfx_channel.play(asset_server.load(sound.as_str()))
+			.with_volume(settings.audible_volume(AudioBus::Sfx) * cue.volume)
+			.with_panning(event.pos.map_or(0.5, |pos| board_panning(pos.x)))
+			.with_playback_rate(pitch);
+	}
+}
 
-		// Old method of randomly selecting sfx
-		//let rn = ((rand::random::<f32>() * 3.0).floor() as usize).clamp(0, 2);
-		//let sfx = asset_server.load(sounds[rn]);
+fn button_sounds(
+	interaction_query: Query<&Interaction, (Changed<Interaction>, With<Button>)>,
+	mut sfx: EventWriter<PlaySfx>,
+) {
+	for interaction in interaction_query.iter() {
+		match interaction {
+			Interaction::Hovered => sfx.send(Sfx::Hover.into()),
+			Interaction::Clicked => sfx.send(Sfx::Click.into()),
+			Interaction::None => (),
+		}
+	}
+}
 
-		let sfx = asset_server.load(*sounds.choose(&mut rand::thread_rng()).unwrap());
-		audio.play(sfx).with_volume(volume.0);
+fn day_transition_sound(
+	mut sfx: EventWriter<PlaySfx>,
+) {
+	sfx.send(Sfx::DayTransition.into());
+}
 
-		ding_timer.0.reset();
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn every_sfx_has_a_sound() {
+		let map = SfxMap::default();
+		for sfx in [
+			Sfx::Launch, Sfx::BasketHit, Sfx::BasketMiss, Sfx::Eat, Sfx::Soup,
+			Sfx::Bell, Sfx::Prize, Sfx::Hover, Sfx::Click, Sfx::DayTransition,
+		] {
+			let cue = map.0.get(&sfx).unwrap_or_else(|| panic!("{:?} is missing from sfx.ron", sfx));
+			assert!(!cue.sounds.is_empty(), "{:?} has no sounds", sfx);
+			for sound in cue.sounds.iter() {
+				let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("assets").join(sound);
+				assert!(path.exists(), "{:?} points at missing {}", sfx, sound);
+			}
+		}
 	}
 }
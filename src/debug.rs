@@ -1,7 +1,7 @@
 use bevy::{prelude::*, window::PrimaryWindow};
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
 
-use crate::game::{states::{GameState, DayState, PauseState}, pegs::Peg, targets::ClearOnDayTransition};
+use crate::{game::{states::{GameState, DayState, PauseState}, pegs::Peg, targets::ClearOnDayTransition}, menu::MenuState};
 
 pub struct DebugPlugin;
 
@@ -21,6 +21,7 @@ impl Plugin for DebugPlugin {
 			set_day_state
 				.run_if(in_state(GameState::Game)),
 			set_game_state
+				.run_if(not(in_state(MenuState::NameEntry))),
 		))
 		;
 		}
@@ -42,7 +43,7 @@ pub fn manually_change_post_processing_settings(
 		settings.strength.wave_distort -= 0.05;
 	}
 	if keyboard.just_pressed(KeyCode::Numpad3) {
-		settings.ripple.start_time = time.elapsed_seconds();
+		settings.ripples.push(time.elapsed_seconds(), 0.5, 0.5, 1.0);
 	}
 	if keyboard.just_pressed(KeyCode::Numpad4) {
 		settings.strength.hue_shift += 0.1;
@@ -4,7 +4,7 @@ use crate::setup::{UserData, despawn_entities_with};
 
 use crate::game::states::{DayState, GameState};
 
-use super::targets::ClearOnDayTransition;
+use super::{targets::ClearOnDayTransition, calendar::Calendar, minigames::Minigames};
 
 pub struct BackgroundsPlugin;
 
@@ -12,9 +12,6 @@ impl Plugin for BackgroundsPlugin {
     fn build(&self, app: &mut App) {
         app
 		.add_system(spawn_title.in_schedule(OnEnter(GameState::Menu)))
-		.add_system(spawn_background.in_schedule(OnEnter(DayState::Morning)))
-		.add_system(spawn_background.in_schedule(OnEnter(DayState::Evening)))
-		.add_system(spawn_background.in_schedule(OnEnter(DayState::Night)))
 		.add_system(despawn_entities_with::<Title>.in_schedule(OnExit(GameState::Menu)))
 		;
 	}
@@ -23,19 +20,18 @@ impl Plugin for BackgroundsPlugin {
 #[derive(Component)]
 pub struct Title;
 
-fn spawn_background(
+pub(crate) fn spawn_background(
 	mut commands: Commands,
 	asset_server: Res<AssetServer>,
 	state: Res<State<DayState>>,
 	user_data: Res<UserData>,
+	calendar: Res<Calendar>,
+	minigames: Res<Minigames>,
 ) {
-	let day = user_data.day % 2.0;
-	let background_sprite = match state.0 {
-		DayState::Dawn => asset_server.load("backgrounds/mart.png"),
-		DayState::Morning => asset_server.load("backgrounds/mart.png"),
-		DayState::Evening => asset_server.load("backgrounds/cart.png"),
-		DayState::Night => asset_server.load(format!("backgrounds/arcade{}.png", day)),
+	let Some(minigame) = minigames.get(state.0) else {
+		return;
 	};
+	let background_sprite: Handle<Image> = asset_server.load((minigame.background)(calendar.day(user_data.day)));
 
 	commands.spawn(
 		(
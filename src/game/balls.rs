@@ -5,7 +5,8 @@ use bevy::{prelude::*, window::PrimaryWindow};
 use bevy_pkv::PkvStore;
 
 use crate::despawn_entities_with;
-use crate::setup::{UserData, SaveData};
+use crate::setup::UserData;
+use crate::audio::sfx::{PlaySfx, Sfx};
 
 use crate::game::states::{DayState, GameState, PauseState};
 
@@ -15,6 +16,7 @@ use super::physics::Gravity;
 use super::physics::Velocity;
 use super::physics::ball_target_collide;
 use super::states::OutOfMoneyTimer;
+use super::minigames::Minigames;
 
 pub const BALL_DEPTH: f32 = -0.9;
 pub const DROPZONE_DEPTH: f32 = -1.5;
@@ -33,19 +35,11 @@ impl Plugin for BallsPlugin {
 		// Game systems
 		.add_system(spawn_ball
 			.run_if(not(in_state(DayState::Dawn)))
-			.run_if(not(in_state(DayState::Morning)))
 			.run_if(in_state(PauseState::Unpaused))
 			.run_if(in_state(GameState::Game)))
 		.add_systems((despawn_ball.after(ball_target_collide), launch_ball).in_set(PachinkoSystemSet))
-		// Spawning systems
-		.add_system(spawn_drop_zone.in_schedule(OnEnter(DayState::Morning)))
-		.add_system(spawn_drop_zone.in_schedule(OnEnter(DayState::Evening)))
-		.add_system(spawn_drop_zone.in_schedule(OnEnter(DayState::Night)))
 		// Despawning systems
 		.add_systems(despawn_systems.in_schedule(OnExit(GameState::Game)))
-		.add_systems(despawn_systems.in_schedule(OnExit(DayState::Morning)))
-		.add_systems(despawn_systems.in_schedule(OnExit(DayState::Evening)))
-		.add_systems(despawn_systems.in_schedule(OnExit(DayState::Night)))
 		;
 	}
 }
@@ -68,7 +62,7 @@ pub struct Held;
 #[derive(Component)]
 pub struct HangTimer(pub Timer);
 
-fn spawn_drop_zone(
+pub(crate) fn spawn_drop_zone(
 	mut commands: Commands,
 	asset_server: Res<AssetServer>,
 ) {
@@ -96,6 +90,7 @@ fn spawn_ball(
 	asset_server: Res<AssetServer>,
 	drop_zone: Res<DropZone>,
 	state: Res<State<DayState>>,
+	minigames: Res<Minigames>,
 	mut user_data: ResMut<UserData>,
 	mut money_timer: ResMut<OutOfMoneyTimer>,
 	time: Res<Time>,
@@ -109,11 +104,8 @@ fn spawn_ball(
 		if let Some(current_pos) = window.cursor_position() {
 			if current_pos.x < drop_zone.max_lim.x && current_pos.x > drop_zone.min_lim.x 
 				&& current_pos.y < drop_zone.max_lim.y && current_pos.y > drop_zone.min_lim.y {
-				let drop_type = match state.0 {
-					DayState::Dawn => DropType::Ball,
-					DayState::Morning => rand::random(),
-					DayState::Evening => DropType::Mouth,
-					DayState::Night => DropType::Ball,
+				let Some(drop_type) = minigames.get(state.0).and_then(|minigame| (minigame.drop_type)()) else {
+					return;
 				};
 				match drop_type {
 					DropType::Ball => {
@@ -207,6 +199,7 @@ fn launch_ball(
 	ball_query: Query<(Entity, &Transform), With<Held>>,
 	window_query: Query<&Window, With<PrimaryWindow>>,
 	mouse: Res<Input<MouseButton>>,
+	mut sfx: EventWriter<PlaySfx>,
 ) {
 	let window = window_query.get_single().unwrap();
 	for (entity, transform) in ball_query.iter() {
@@ -218,6 +211,7 @@ fn launch_ball(
 					(transform.translation.x - current_pos.x) * 4.0, 
 					(transform.translation.y - current_pos.y) * 4.0)
 					.clamp_length(0.0, 512.0)));
+				sfx.send(PlaySfx::at(Sfx::Launch, transform.translation.truncate()));
 			}
 		}
 	}
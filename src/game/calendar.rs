@@ -0,0 +1,140 @@
+// Which time slots each day has, and what happens on them
+// The week repeats from day 0 onwards, event days replace whichever weekday they land on
+
+use bevy::prelude::*;
+
+use super::states::DayState;
+
+pub const RENT: f32 = 20.0;
+
+// Time slots, night arcade and dialogue of a single day
+#[derive(Clone, Debug)]
+pub struct DaySchedule {
+	pub name: &'static str,
+	// In the order they're played, needs at least one
+	pub slots: Vec<DayState>,
+	// Which arcade the night is spent in, picks the background and dialogue
+	pub arcade: usize,
+	// Replaces the usual dialogue for the whole day
+	pub dialogue: Option<&'static str>,
+}
+
+impl DaySchedule {
+	fn weekday(name: &'static str, arcade: usize) -> Self {
+		Self {
+			name,
+			slots: vec![DayState::Morning, DayState::Evening, DayState::Night],
+			arcade,
+			dialogue: None,
+		}
+	}
+
+	// No work at the weekend
+	fn weekend(name: &'static str, arcade: usize) -> Self {
+		Self {
+			name,
+			slots: vec![DayState::Evening, DayState::Night],
+			arcade,
+			dialogue: None,
+		}
+	}
+}
+
+#[derive(Resource, Clone, Debug)]
+pub struct Calendar {
+	pub week: Vec<DaySchedule>,
+	pub events: Vec<(usize, DaySchedule)>,
+	// Weekdays, as indices into week, on which rent is taken at the start of the day
+	pub rent_days: Vec<usize>,
+	pub rent: f32,
+}
+
+impl Default for Calendar {
+	fn default() -> Self {
+		Self {
+			week: vec![
+				DaySchedule::weekend("Sunday", 0),
+				DaySchedule::weekday("Monday", 1),
+				DaySchedule::weekday("Tuesday", 0),
+				DaySchedule::weekday("Wednesday", 1),
+				DaySchedule::weekday("Thursday", 0),
+				DaySchedule::weekday("Friday", 1),
+				DaySchedule::weekend("Saturday", 0),
+			],
+			// None written yet, an event is a day number and the schedule replacing it, like
+			// (12, DaySchedule {name: "...", slots: vec![DayState::Night], arcade: 1, dialogue: Some("...")})
+			events: vec![],
+			rent_days: vec![0],
+			rent: RENT,
+		}
+	}
+}
+
+impl Calendar {
+	pub fn day(&self, day: f32) -> &DaySchedule {
+		let day = day as usize;
+		self.events.iter()
+			.find(|(event_day, _)| *event_day == day)
+			.map(|(_, schedule)| schedule)
+			.unwrap_or(&self.week[day % self.week.len()])
+	}
+
+	pub fn rent_due(&self, day: f32) -> bool {
+		self.rent_days.contains(&(day as usize % self.week.len()))
+	}
+
+	// Day and time slot that follow the current one, Dawn leads into the first slot of the day
+	pub fn next_slot(&self, day: f32, current: DayState) -> (f32, DayState) {
+		let slots = &self.day(day).slots;
+		let next = if current == DayState::Dawn {
+			slots.first()
+		} else {
+			slots.iter().skip_while(|slot| **slot != current).nth(1)
+		};
+		match next {
+			Some(slot) => (day, *slot),
+			None => (day + 1.0, self.day(day + 1.0).slots.first().copied().unwrap_or(DayState::Night)),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn calendar_with_event() -> Calendar {
+		let mut calendar = Calendar::default();
+		calendar.events.push((8, DaySchedule {
+			name: "Festival",
+			slots: vec![DayState::Evening],
+			arcade: 1,
+			dialogue: Some("festival"),
+		}));
+		calendar
+	}
+
+	#[test]
+	fn events_replace_their_weekday() {
+		let calendar = calendar_with_event();
+		assert_eq!(calendar.day(8.0).name, "Festival");
+		// The same weekday a week earlier is untouched
+		assert_eq!(calendar.day(1.0).name, "Monday");
+	}
+
+	#[test]
+	fn event_days_play_their_own_slots() {
+		let calendar = calendar_with_event();
+		assert_eq!(calendar.next_slot(8.0, DayState::Dawn), (8.0, DayState::Evening));
+		// Skipping the Monday morning and night the event replaced
+		assert_eq!(calendar.next_slot(7.0, DayState::Night), (8.0, DayState::Evening));
+		assert_eq!(calendar.next_slot(8.0, DayState::Evening), (9.0, DayState::Morning));
+	}
+
+	#[test]
+	fn rent_is_due_every_sunday() {
+		let calendar = Calendar::default();
+		assert!(calendar.rent_due(0.0));
+		assert!(calendar.rent_due(14.0));
+		assert!(!(1..7).any(|day| calendar.rent_due(day as f32)));
+	}
+}
@@ -1,7 +1,12 @@
+use std::{collections::BTreeMap, sync::OnceLock};
+
 use bevy::prelude::*;
-use rand::{Rng, thread_rng, prelude::Distribution, distributions::Standard};
+use rand::{Rng, prelude::Distribution, distributions::Standard};
+use serde::{Serialize, Deserialize};
+
+use crate::setup::UserData;
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
 pub enum DropCategory {
 	Fruit,
 	Veg,
@@ -12,323 +17,162 @@ pub enum DropCategory {
 	Special,
 }
 
-#[derive(Component, PartialEq, Eq, Clone, Copy)]
+// Everything about a droppable lives in assets/data/droppables.ron
+#[derive(Component, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Serialize, Deserialize)]
 pub enum DropType {
-	Apple, //Fruit
-	Bagel, //Bakery
-	Ball, //Special
-	Bell, //Special
-	Bun, //Bakery
-	Cassette, //Tech
-	Cauliflower, //Veg
-	Cd, //Tech
-	Cheese, //Produce
-	Cherry, //Fruit
-	Crown, //Special
-	Diamond, //Special
-	Donut, //Bakery
-	Egg, //Produce
-	FriedEgg, //Produce
-	Garlic, //Veg
-	Grapes, //Fruit
-	Headphones, //Tech
-	Lemon, //Lemon
-	Lettuce, //Veg
-	Money, //Special
-	Mouth, //Special
-	Mushroom, //Drug
-	Onion, //Veg
-	Orange, //Fruit
-	Pepper, //Fancy
-	Pill, //Drug
-	Pumpkin, //Fruit
-	Ramen, //Fancy
-	Salt, //Fancy
-	Seven, //Special
-	Shot, //Drug
-	Weed, //Drug
+	Apple,
+	Bagel,
+	Ball,
+	Bell,
+	Bun,
+	Cassette,
+	Cauliflower,
+	Cd,
+	Cheese,
+	Cherry,
+	Crown,
+	Diamond,
+	Donut,
+	Egg,
+	FriedEgg,
+	Garlic,
+	Grapes,
+	Headphones,
+	Lemon,
+	Lettuce,
+	Money,
+	Mouth,
+	Mushroom,
+	Onion,
+	Orange,
+	Pepper,
+	Pill,
+	Pumpkin,
+	Ramen,
+	Salt,
+	Seven,
+	Shot,
+	Weed,
 }
 
-impl Distribution<DropType> for Standard {
-	fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> DropType {
-		match rng.gen_range(0..20) {
-			0 => DropType::Apple,
-			1 => DropType::Bagel,
-			2 => DropType::Bun,
-			3 => DropType::Cassette,
-			4 => DropType::Cauliflower,
-			5 => DropType::Cd,
-			6 => DropType::Cheese,
-			20 => DropType::Cherry,
-			7 => DropType::Donut,
-			8 => DropType::Egg,
-			9 => DropType::FriedEgg,
-			10 => DropType::Garlic,
-			11 => DropType::Grapes,
-			12 => DropType::Headphones,
-			21 => DropType::Lemon,
-			13 => DropType::Lettuce,
-			14 => DropType::Onion,
-			15 => DropType::Orange,
-			16 => DropType::Pepper,
-			17 => DropType::Pumpkin,
-			18 => DropType::Ramen,
-			19 => DropType::Salt,
-			//2 => DropType::Ball,
-			//15 => DropType::Mushroom,
-			//19 => DropType::Pill,
-			//23 => DropType::Shot,
-			//24 => DropType::Weed,
-			_ => DropType::Ball,
+#[derive(Deserialize, Clone, Debug)]
+pub struct DropInfo {
+	pub sprite: String,
+	pub category: DropCategory,
+	pub edible: bool,
+	// Chance of turning up in a random draw compared to the others, 0 for never
+	#[serde(default)]
+	pub weight: u32,
+	#[serde(default)]
+	pub physics: DropPhysics,
+	// What biting it does to you
+	#[serde(default)]
+	pub eat: StatEffects,
+	#[serde(default)]
+	pub tags: Vec<String>,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(default)]
+pub struct DropPhysics {
+	//50.0 is normalish, 100.0 very bouncy
+	pub bounciness: f32,
+	// Multiplier on the board gravity
+	pub gravity: f32,
+}
+
+impl Default for DropPhysics {
+	fn default() -> Self {
+		Self {
+			bounciness: 80.0,
+			gravity: 1.0,
 		}
 	}
 }
 
-impl DropType {
-	pub fn get_path(&self) -> &'static str {
-		match self {
-			DropType::Apple => "droppables/apple.png",
-			DropType::Bagel => "droppables/bagel.png",
-			DropType::Ball => "droppables/ball.png",
-			DropType::Bell => "droppables/bell.png",
-			DropType::Bun => "droppables/bun.png",
-			DropType::Cassette => "droppables/cassette.png",
-			DropType::Cauliflower => "droppables/cauliflower.png",
-			DropType::Cd => "droppables/cd.png",
-			DropType::Cheese => "droppables/cheese.png",
-			DropType::Cherry => "droppables/cherry.png",
-			DropType::Crown => "droppables/crown.png",
-			DropType::Diamond => "droppables/diamond.png",
-			DropType::Donut => "droppables/donut.png",
-			DropType::Egg => "droppables/egg.png",
-			DropType::FriedEgg => "droppables/fried_egg.png",
-			DropType::Garlic => "droppables/garlic.png",
-			DropType::Grapes => "droppables/grapes.png",
-			DropType::Headphones => "droppables/headphones.png",
-			DropType::Lemon => "droppables/lemon.png",
-			DropType::Lettuce => "droppables/lettuce.png",
-			DropType::Money => "droppables/money.png",
-			DropType::Mouth => "droppables/mouth.png",
-			DropType::Mushroom => "droppables/mushroom.png",
-			DropType::Onion => "droppables/onion.png",
-			DropType::Orange => "droppables/orange.png",
-			DropType::Pepper => "droppables/onion.png",
-			DropType::Pill => "droppables/pill.png",
-			DropType::Pumpkin => "droppables/pumpkin.png",
-			DropType::Ramen => "droppables/ramen.png",
-			DropType::Salt => "droppables/salt.png",
-			DropType::Seven => "droppables/seven.png",
-			DropType::Shot => "droppables/shot.png",
-			DropType::Weed => "droppables/weed.png",
-		}
+#[derive(Deserialize, Clone, Copy, Default, Debug)]
+#[serde(default)]
+pub struct StatEffects {
+	pub stress: f32,
+	pub royal: f32,
+	pub money: f32,
+	pub drugs: f32,
+}
+
+impl StatEffects {
+	pub fn apply(&self, user_data: &mut UserData) {
+		user_data.stress = (user_data.stress + self.stress).clamp(0.0, 100.0);
+		user_data.royal = (user_data.royal + self.royal).clamp(0.0, 100.0);
+		user_data.money = (user_data.money + self.money).clamp(0.0, 100.0);
+		user_data.drugs_taken += self.drugs;
 	}
+}
 
-	pub fn get_type(&self) -> DropCategory {
-		match self {
-			DropType::Apple => DropCategory::Fruit,
-			DropType::Bagel => DropCategory::Bakery,
-			DropType::Ball => DropCategory::Special,
-			DropType::Bell => DropCategory::Fruit,
-			DropType::Bun => DropCategory::Bakery,
-			DropType::Cassette => DropCategory::Tech,
-			DropType::Cauliflower => DropCategory::Veg,
-			DropType::Cd => DropCategory::Tech,
-			DropType::Cheese => DropCategory::Produce,
-			DropType::Cherry => DropCategory::Fruit,
-			DropType::Crown => DropCategory::Special,
-			DropType::Diamond => DropCategory::Special,
-			DropType::Donut => DropCategory::Bakery,
-			DropType::Egg => DropCategory::Produce,
-			DropType::FriedEgg => DropCategory::Produce,
-			DropType::Garlic => DropCategory::Veg,
-			DropType::Grapes => DropCategory::Fruit,
-			DropType::Headphones => DropCategory::Tech,
-			DropType::Lemon => DropCategory::Fruit,
-			DropType::Lettuce => DropCategory::Veg,
-			DropType::Money => DropCategory::Special,
-			DropType::Mouth => DropCategory::Special,
-			DropType::Mushroom => DropCategory::Drug,
-			DropType::Onion => DropCategory::Veg,
-			DropType::Orange => DropCategory::Fruit,
-			DropType::Pepper => DropCategory::Special,
-			DropType::Pill => DropCategory::Drug,
-			DropType::Pumpkin => DropCategory::Veg,
-			DropType::Ramen => DropCategory::Special,
-			DropType::Salt => DropCategory::Special,
-			DropType::Seven => DropCategory::Special,
-			DropType::Shot => DropCategory::Drug,
-			DropType::Weed => DropCategory::Drug,
-		}
+#[derive(Debug)]
+pub struct DropRegistry(pub BTreeMap<DropType, DropInfo>);
+
+impl DropRegistry {
+	pub fn parse(source: &str) -> Result<Self, ron::error::SpannedError> {
+		ron::from_str(source).map(DropRegistry)
 	}
 
-	pub fn is_edible(&self) -> bool {
-		match self {
-			DropType::Apple => true,
-			DropType::Bagel => true,
-			DropType::Ball => false,
-			DropType::Bell => false,
-			DropType::Bun => true,
-			DropType::Cassette => false,
-			DropType::Cauliflower => true,
-			DropType::Cd => false,
-			DropType::Cheese => true,
-			DropType::Cherry => true,
-			DropType::Crown => false,
-			DropType::Diamond => false,
-			DropType::Donut => true,
-			DropType::Egg => true,
-			DropType::FriedEgg => true,
-			DropType::Garlic => true,
-			DropType::Grapes => true,
-			DropType::Headphones => false,
-			DropType::Lemon => true,
-			DropType::Lettuce => true,
-			DropType::Money => false,
-			DropType::Mouth => false,
-			DropType::Mushroom => true,
-			DropType::Onion => true,
-			DropType::Orange => true,
-			DropType::Pepper => true,
-			DropType::Pill => true,
-			DropType::Pumpkin => true,
-			DropType::Ramen => true,
-			DropType::Salt => true,
-			DropType::Seven => false,
-			DropType::Shot => true,
-			DropType::Weed => true,
-		}
+	pub fn get(&self, drop_type: DropType) -> &DropInfo {
+		self.0.get(&drop_type).unwrap_or_else(|| panic!("{:?} is missing from droppables.ron", drop_type))
 	}
 }
 
-pub fn random_drug() -> DropType {
-	let mut rng = thread_rng();
-	let index: u8 = rng.gen_range(0..4);
-	match index {
-		0 => DropType::Mushroom,
-		1 => DropType::Pill,
-		2 => DropType::Shot,
-		3 => DropType::Weed,
-		_ => DropType::Ball,
-	}
+// Baked into the binary so the table is there before any asset has loaded
+pub fn registry() -> &'static DropRegistry {
+	static REGISTRY: OnceLock<DropRegistry> = OnceLock::new();
+	REGISTRY.get_or_init(|| {
+		DropRegistry::parse(include_str!("../../assets/data/droppables.ron"))
+			.expect("assets/data/droppables.ron is malformed")
+	})
 }
 
-pub fn random_droppable_except_mouth_ball_and_seven() -> DropType {
-	let mut rng = thread_rng();
-	let index: u8 = rng.gen_range(0..30);
-	match index {
-		0 => DropType::Apple,
-		1 => DropType::Bagel,
-		//2 => DropType::Ball,
-		3 => DropType::Bell,
-		4 => DropType::Bun,
-		5 => DropType::Cassette,
-		6 => DropType::Cauliflower,
-		7 => DropType::Cd,
-		8 => DropType::Cheese,
-		9 => DropType::Cherry,
-		10 => DropType::Crown,
-		11 => DropType::Diamond,
-		12 => DropType::Donut,
-		13 => DropType::Egg,
-		14 => DropType::FriedEgg,
-		15 => DropType::Garlic,
-		16 => DropType::Grapes,
-		17 => DropType::Headphones,
-		18 => DropType::Lemon,
-		19 => DropType::Lettuce,
-		//20 => DropType::Mouth,
-		21 => DropType::Mushroom,
-		22 => DropType::Onion,
-		23 => DropType::Orange,
-		24 => DropType::Pepper,
-		25 => DropType::Pill,
-		26 => DropType::Pumpkin,
-		27 => DropType::Ramen,
-		2 => DropType::Salt,
-		//29 => DropType::Seven,
-		20 => DropType::Shot,
-		29 => DropType::Weed,
-		_ => DropType::Apple,
+impl Distribution<DropType> for Standard {
+	fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> DropType {
+		let total: u32 = registry().0.values().map(|info| info.weight).sum();
+		let mut roll = rng.gen_range(0..total);
+		for (drop_type, info) in registry().0.iter() {
+			if roll < info.weight {
+				return *drop_type;
+			}
+			roll -= info.weight;
+		}
+		DropType::Ball
 	}
 }
 
-pub fn random_non_drug_edible() -> DropType {
-	let mut rng = thread_rng();
-	let index: u8 = rng.gen_range(0..18);
-	match index {
-		0 => DropType::Apple,
-		1 => DropType::Bagel,
-		//2 => DropType::Ball,
-		//3 => DropType::Bell,
-		2 => DropType::Bun,
-		//5 => DropType::Cassette,
-		3 => DropType::Cauliflower,
-		//7 => DropType::Cd,
-		4 => DropType::Cheese,
-		5 => DropType::Cherry,
-		//10 => DropType::Crown,
-		//11 => DropType::Diamond,
-		6 => DropType::Donut,
-		7 => DropType::Egg,
-		8 => DropType::FriedEgg,
-		9 => DropType::Garlic,
-		10 => DropType::Grapes,
-		//17 => DropType::Headphones,
-		11 => DropType::Lemon,
-		12 => DropType::Lettuce,
-		//20 => DropType::Mouth,
-		//13 => DropType::Mushroom,
-		13 => DropType::Onion,
-		14 => DropType::Orange,
-		15 => DropType::Pepper,
-		//25 => DropType::Pill,
-		16 => DropType::Pumpkin,
-		17 => DropType::Ramen,
-		//28 => DropType::Salt,
-		//29 => DropType::Seven,
-		//30 => DropType::Shot,
-		//31 => DropType::Weed,
-		_ => DropType::Apple,
+impl DropType {
+	pub fn info(&self) -> &'static DropInfo {
+		registry().get(*self)
+	}
+
+	pub fn get_path(&self) -> &'static str {
+		&self.info().sprite
+	}
+
+	pub fn get_type(&self) -> DropCategory {
+		self.info().category
+	}
+
+	pub fn is_edible(&self) -> bool {
+		self.info().edible
 	}
 }
 
-pub fn random_inedible_except_special() -> DropType {
-	let mut rng = thread_rng();
-	let index: u8 = rng.gen_range(0..7);
-	match index {
-		//0 => DropType::Apple,
-		//1 => DropType::Bagel,
-		//2 => DropType::Ball,
-		0 => DropType::Bell,
-		//4 => DropType::Bun,
-		1 => DropType::Cassette,
-		//6 => DropType::Cauliflower,
-		2 => DropType::Cd,
-		//8 => DropType::Cheese,
-		//9 => DropType::Cherry,
-		3 => DropType::Crown,
-		4 => DropType::Diamond,
-		//12 => DropType::Donut,
-		//13 => DropType::Egg,
-		//14 => DropType::FriedEgg,
-		//15 => DropType::Garlic,
-		//16 => DropType::Grapes,
-		5 => DropType::Headphones,
-		//18 => DropType::Lemon,
-		//19 => DropType::Lettuce,
-		6 => DropType::Money,
-		//20 => DropType::Mouth,
-		//21 => DropType::Mushroom,
-		//22 => DropType::Onion,
-		//23 => DropType::Orange,
-		//24 => DropType::Pepper,
-		//25 => DropType::Pill,
-		//26 => DropType::Pumpkin,
-		//27 => DropType::Ramen,
-		//28 => DropType::Salt,
-		//29 => DropType::Seven,
-		//30 => DropType::Shot,
-		//31 => DropType::Weed,
-		_ => DropType::Cd,
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn every_droppable_has_a_sprite() {
+		// One entry for each of the 33 variants
+		assert_eq!(registry().0.len(), 33);
+		for (drop_type, info) in registry().0.iter() {
+			let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("assets").join(&info.sprite);
+			assert!(path.exists(), "{:?} points at missing {}", drop_type, info.sprite);
+		}
 	}
-}
\ No newline at end of file
+}
@@ -0,0 +1,172 @@
+// Weighted tables for drawing random droppables
+// Each rule adds weight to every item its filter matches, items no rule matches never turn up
+// Rules can grow or shrink with the day, so a table can change over the week
+
+use rand::Rng;
+
+use super::food::{registry, DropCategory, DropInfo, DropType};
+
+#[derive(Debug)]
+pub enum Filter {
+	Item(DropType),
+	Category(DropCategory),
+	Edible(bool),
+	// Matches a tag in droppables.ron
+	Tag(&'static str),
+	Not(&'static Filter),
+	All(&'static [Filter]),
+}
+
+impl Filter {
+	pub fn matches(&self, drop_type: DropType, info: &DropInfo) -> bool {
+		match self {
+			Filter::Item(item) => drop_type == *item,
+			Filter::Category(category) => info.category == *category,
+			Filter::Edible(edible) => info.edible == *edible,
+			Filter::Tag(tag) => info.tags.iter().any(|item_tag| item_tag == tag),
+			Filter::Not(filter) => !filter.matches(drop_type, info),
+			Filter::All(filters) => filters.iter().all(|filter| filter.matches(drop_type, info)),
+		}
+	}
+}
+
+#[derive(Debug)]
+pub struct LootRule {
+	pub filter: Filter,
+	pub weight: f32,
+	// Added to the weight for every day played
+	pub per_day: f32,
+}
+
+#[derive(Debug)]
+pub struct LootTable(pub &'static [LootRule]);
+
+impl LootTable {
+	// Chance of each item on the given day, leaving out the ones that can't turn up
+	pub fn weights(&self, day: f32) -> Vec<(DropType, f32)> {
+		registry().0.iter()
+			.map(|(drop_type, info)| {
+				let weight: f32 = self.0.iter()
+					.filter(|rule| rule.filter.matches(*drop_type, info))
+					.map(|rule| (rule.weight + rule.per_day * day).max(0.0))
+					.sum();
+				(*drop_type, weight)
+			})
+			.filter(|(_, weight)| *weight > 0.0)
+			.collect()
+	}
+
+	pub fn draw<R: Rng + ?Sized>(&self, day: f32, rng: &mut R) -> DropType {
+		let weights = self.weights(day);
+		let total: f32 = weights.iter().map(|(_, weight)| weight).sum();
+		let mut roll = rng.gen_range(0.0..total);
+		for (drop_type, weight) in weights.iter() {
+			if roll < *weight {
+				return *drop_type;
+			}
+			roll -= weight;
+		}
+		// Only reached through float rounding on the last item
+		weights.last().map(|(drop_type, _)| *drop_type).unwrap_or(DropType::Ball)
+	}
+}
+
+// Edible pegs of the dinner board, the fancy recipe items get more common as the week goes on
+pub const MEALS: LootTable = LootTable(&[
+	LootRule {
+		filter: Filter::All(&[Filter::Edible(true), Filter::Not(&Filter::Category(DropCategory::Drug))]),
+		weight: 1.0,
+		per_day: 0.0,
+	},
+	LootRule {
+		filter: Filter::All(&[Filter::Edible(true), Filter::Category(DropCategory::Special)]),
+		weight: 0.0,
+		per_day: 0.25,
+	},
+]);
+
+// Inedible pegs along the walls of the dinner board
+pub const JUNK: LootTable = LootTable(&[
+	LootRule {
+		filter: Filter::All(&[Filter::Edible(false), Filter::Not(&Filter::Tag("machine"))]),
+		weight: 1.0,
+		per_day: 0.0,
+	},
+]);
+
+// Shot out of the parlor machine during fever
+pub const PRIZES: LootTable = LootTable(&[
+	LootRule {
+		filter: Filter::Item(DropType::Diamond),
+		weight: 0.4,
+		per_day: 0.0,
+	},
+	LootRule {
+		filter: Filter::All(&[Filter::Tag("prize"), Filter::Not(&Filter::Item(DropType::Diamond))]),
+		weight: 0.3,
+		per_day: 0.0,
+	},
+]);
+
+#[cfg(test)]
+mod tests {
+	use rand::{SeedableRng, rngs::StdRng};
+
+	use super::*;
+
+	// Draws from the table and checks every item turns up about as often as its weight says
+	fn check_distribution(table: &LootTable, day: f32) {
+		const DRAWS: usize = 100_000;
+		let weights = table.weights(day);
+		let total: f32 = weights.iter().map(|(_, weight)| weight).sum();
+		let mut rng = StdRng::seed_from_u64(42);
+		let mut counts = vec![0usize; weights.len()];
+		for _ in 0..DRAWS {
+			let drop_type = table.draw(day, &mut rng);
+			let index = weights.iter().position(|(item, _)| *item == drop_type)
+				.unwrap_or_else(|| panic!("{:?} isn't in the table", drop_type));
+			counts[index] += 1;
+		}
+		for ((drop_type, weight), count) in weights.iter().zip(counts) {
+			let expected = weight / total;
+			let seen = count as f32 / DRAWS as f32;
+			assert!((seen - expected).abs() < 0.01, "{:?} came up {} of the time instead of {}", drop_type, seen, expected);
+		}
+	}
+
+	#[test]
+	fn meals_are_edible_and_drug_free() {
+		let weights = MEALS.weights(0.0);
+		assert_eq!(weights.len(), 19);
+		for (drop_type, weight) in weights {
+			assert!(drop_type.is_edible() && drop_type.get_type() != DropCategory::Drug);
+			assert_eq!(weight, 1.0);
+		}
+		check_distribution(&MEALS, 0.0);
+	}
+
+	#[test]
+	fn meals_get_fancier_with_the_days() {
+		let ramen = |day| MEALS.weights(day).into_iter().find(|(item, _)| *item == DropType::Ramen).unwrap().1;
+		assert!(ramen(4.0) > ramen(0.0));
+		check_distribution(&MEALS, 6.0);
+	}
+
+	#[test]
+	fn junk_leaves_out_the_machine() {
+		let weights = JUNK.weights(0.0);
+		let items: Vec<DropType> = weights.iter().map(|(item, _)| *item).collect();
+		assert_eq!(items, vec![
+			DropType::Bell, DropType::Cassette, DropType::Cd, DropType::Crown,
+			DropType::Diamond, DropType::Headphones, DropType::Money,
+		]);
+		check_distribution(&JUNK, 0.0);
+	}
+
+	#[test]
+	fn prizes_favour_diamonds() {
+		let weights = PRIZES.weights(0.0);
+		assert_eq!(weights, vec![(DropType::Crown, 0.3), (DropType::Diamond, 0.4), (DropType::Money, 0.3)]);
+		check_distribution(&PRIZES, 0.0);
+	}
+}
@@ -0,0 +1,257 @@
+// Fever mode of the night parlor
+// Winning the slot machine lottery opens the attacker gate for a number of rounds, balls caught by it pay out big
+// A round ends once enough balls are caught or the gate has been open long enough, like on real machines
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+	game::{
+		balls::{BALL_DEPTH, Ball}, loot::PRIZES, physics::{BallTargetHit, Velocity},
+		states::{DayState, GameState, PauseState}, targets::{ClearOnDayTransition, Target, LABEL_TARGET_DEPTH},
+		PachinkoSystemSet,
+	},
+	setup::UserData,
+	audio::sfx::{PlaySfx, Sfx},
+};
+
+use super::{Minigames, MinigameSet};
+
+pub struct FeverPlugin;
+
+impl Plugin for FeverPlugin {
+	fn build(&self, app: &mut App) {
+		app
+		.add_event::<Jackpot>()
+		.init_resource::<FeverSpec>()
+		.init_resource::<Fever>()
+		.add_systems((reset_fever, spawn_attacker_gate).in_schedule(OnEnter(DayState::Night)))
+		.add_system(reset_fever.in_schedule(OnExit(DayState::Night)))
+		.add_system(start_fever
+			.run_if(on_event::<Jackpot>())
+			.in_set(MinigameSet(DayState::Night)))
+		.add_system(catch_fever_balls
+			.run_if(on_event::<BallTargetHit>())
+			.in_set(MinigameSet(DayState::Night))
+			.in_set(PachinkoSystemSet))
+		.add_system(run_fever
+			.in_set(MinigameSet(DayState::Night))
+			.after(start_fever)
+			.after(catch_fever_balls))
+		;
+	}
+}
+
+// Sent when the lottery is won
+pub struct Jackpot;
+
+// How the machine plays its fever, the numbers printed on a real machine's spec sheet
+#[derive(Resource, Deserialize, Clone, Debug)]
+pub struct FeverSpec {
+	// Chance of each slot machine spin winning, real machines sit around 1 in 320
+	pub jackpot_odds: f64,
+	pub rounds: u32,
+	// Balls caught before a round ends early
+	pub round_count: u32,
+	// Seconds the gate stays open each round at most, and closed between rounds
+	pub gate_open_time: f32,
+	pub gate_closed_time: f32,
+	pub gate_x: f32,
+	pub gate_width: f32,
+	// Money for each ball caught by the gate
+	pub payout: f32,
+}
+
+impl FeverSpec {
+	pub fn parse(source: &str) -> Result<Self, ron::error::SpannedError> {
+		ron::from_str(source)
+	}
+}
+
+// Baked in like droppables.ron
+impl Default for FeverSpec {
+	fn default() -> Self {
+		FeverSpec::parse(include_str!("../../../assets/data/fever.ron"))
+			.expect("assets/data/fever.ron is malformed")
+	}
+}
+
+#[derive(Resource, Default)]
+pub struct Fever {
+	// Rounds left including the current one, 0 outside of fever
+	pub rounds_left: u32,
+	pub gate_open: bool,
+	// Seconds since the gate last opened or closed
+	pub gate_time: f32,
+	// Balls caught this round
+	pub caught: u32,
+	// Set by ringing every bell, the night ends with this fever
+	pub finale: bool,
+}
+
+impl Fever {
+	pub fn active(&self) -> bool {
+		self.rounds_left > 0
+	}
+}
+
+#[derive(Component)]
+pub struct AttackerGate;
+
+fn reset_fever(
+	mut fever: ResMut<Fever>,
+) {
+	*fever = Fever::default();
+}
+
+fn spawn_attacker_gate(
+	mut commands: Commands,
+	asset_server: Res<AssetServer>,
+	spec: Res<FeverSpec>,
+	minigames: Res<Minigames>,
+) {
+	let height = minigames.get(DayState::Night).map(|minigame| minigame.targets.height).unwrap_or(0.0);
+	commands.spawn(
+		(
+			SpriteBundle {
+				// Rim sprite is as wide as the parlor holes
+				transform: Transform::from_xyz(spec.gate_x, height, LABEL_TARGET_DEPTH)
+					.with_scale(Vec3::new(spec.gate_width / 200.0, 1.0, 1.0)),
+				texture: asset_server.load("sprites/rim.png"),
+				sprite: Sprite {
+					color: Color::GOLD,
+					..default()
+				},
+				visibility: Visibility::Hidden,
+				..default()
+			},
+			AttackerGate,
+			ClearOnDayTransition,
+		)
+	);
+}
+
+fn start_fever(
+	mut jackpots: EventReader<Jackpot>,
+	mut fever: ResMut<Fever>,
+	spec: Res<FeverSpec>,
+) {
+	for _ in jackpots.iter() {
+		// The music table switches to the fever track while it's active
+		if !fever.active() {
+			fever.gate_open = false;
+			fever.gate_time = 0.0;
+			fever.caught = 0;
+		}
+		// Another win during fever stacks more rounds on top
+		fever.rounds_left += spec.rounds;
+	}
+}
+
+#[allow(clippy::too_many_arguments)]
+fn catch_fever_balls(
+	mut commands: Commands,
+	mut collision_events: EventReader<BallTargetHit>,
+	gate_query: Query<(), With<AttackerGate>>,
+	mut fever: ResMut<Fever>,
+	spec: Res<FeverSpec>,
+	mut user_data: ResMut<UserData>,
+	asset_server: Res<AssetServer>,
+	mut sfx: EventWriter<PlaySfx>,
+) {
+	for ball_event in collision_events.iter() {
+		if gate_query.get(ball_event.target).is_ok() {
+			fever.caught += 1;
+			user_data.money = (user_data.money + spec.payout).clamp(0.0, 100.0);
+			launch_prize(&mut commands, &asset_server, user_data.day);
+			sfx.send(PlaySfx::at(Sfx::Prize, PRIZE_LAUNCH));
+		}
+	}
+}
+
+// Where prizes come out of the machine
+const PRIZE_LAUNCH: Vec2 = Vec2::new(1300.0, 700.0);
+
+// Shoots a prize out of the machine and back onto the board
+fn launch_prize(
+	commands: &mut Commands,
+	asset_server: &Res<AssetServer>,
+	day: f32,
+) {
+	let (x_rand,y_rand): (f32,f32) = rand::random();
+	let prize = PRIZES.draw(day, &mut rand::thread_rng());
+	commands.spawn(
+		(
+			SpriteBundle {
+				transform: Transform::from_xyz(PRIZE_LAUNCH.x, PRIZE_LAUNCH.y, BALL_DEPTH),
+				texture: asset_server.load(prize.get_path()),
+				..default()
+			},
+			Ball,
+			Velocity(Vec2::new(-(300.0 + x_rand * 150.0), 250.0 + y_rand * 50.0)),
+			prize,
+		)
+	);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_fever(
+	mut commands: Commands,
+	mut fever: ResMut<Fever>,
+	spec: Res<FeverSpec>,
+	mut gate_query: Query<(Entity, &mut Visibility), With<AttackerGate>>,
+	time: Res<Time>,
+	mut next_game_state: ResMut<NextState<GameState>>,
+	mut next_pause_state: ResMut<NextState<PauseState>>,
+	mut next_day_state: ResMut<NextState<DayState>>,
+) {
+	if !fever.active() {
+		return;
+	}
+	fever.gate_time += time.delta_seconds();
+	let open = if fever.gate_open {
+		fever.gate_time < spec.gate_open_time && fever.caught < spec.round_count
+	} else {
+		fever.gate_time >= spec.gate_closed_time
+	};
+	if open == fever.gate_open {
+		return;
+	}
+	fever.gate_open = open;
+	fever.gate_time = 0.0;
+	for (entity, mut visibility) in gate_query.iter_mut() {
+		if open {
+			*visibility = Visibility::Visible;
+			commands.entity(entity).insert(Target {radius: 0.5 * spec.gate_width});
+		} else {
+			*visibility = Visibility::Hidden;
+			commands.entity(entity).remove::<Target>();
+		}
+	}
+	if open {
+		return;
+	}
+	fever.caught = 0;
+	fever.rounds_left -= 1;
+	if fever.active() {
+		return;
+	}
+	if fever.finale {
+		next_day_state.0 = Some(DayState::Dawn);
+		next_pause_state.0 = Some(PauseState::Initial);
+		next_game_state.0 = Some(GameState::Outro);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn fever_spec_can_be_played() {
+		let spec = FeverSpec::default();
+		assert!(spec.jackpot_odds > 0.0 && spec.jackpot_odds <= 1.0, "jackpot_odds is not a chance");
+		assert!(spec.rounds > 0 && spec.round_count > 0, "fever has no rounds to play");
+		assert!(spec.gate_open_time > 0.0 && spec.gate_width > 0.0, "the gate never lets a ball in");
+	}
+}
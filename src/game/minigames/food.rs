@@ -3,19 +3,20 @@ use bevy::prelude::*;
 use crate::{
 	game::{
 		physics::{BallTargetHit, Velocity, BallCollisionEvent, BallCollisionEventType},
-		food::{DropCategory, random_inedible_except_special, random_non_drug_edible, DropType},
-		targets::{Bowl, ClearOnDayTransition}, PachinkoSystemSet,
-		pegs::{Peg, PEG_DEPTH, PegType, PegType::ItemPeg, PegType::PachinkoPeg},
-		states::{DayState, PauseState, GameState}
-	}, setup::UserData
+		food::{DropCategory, DropType}, loot::{JUNK, MEALS},
+		targets::{Bowl, Target, ClearOnDayTransition, TargetDetails, TargetPair}, PachinkoSystemSet,
+		pegs::{Peg, PEG_DEPTH, PegType, PegType::ItemPeg, PegType::PachinkoPeg, BoardIndex},
+		states::DayState
+	}, setup::UserData, save::autosave::{AutosaveSet, RestoreLevel, SavedPeg}, audio::sfx::{PlaySfx, Sfx},
+	vfx::particles::{ParticleBurst, Emitter},
 };
 
-use super::parlor::{DrugPeg, HORIZONTAL_SPEED, VERTICAL_SPEED};
+use super::{parlor::DrugPeg, HORIZONTAL_SPEED, VERTICAL_SPEED, Minigame, MinigameDetails, MinigameSet,
+	recipes::{RecipesPlugin, RecipeBook, SoupPot, Mouthful, spawn_soup_items}};
 
 pub struct FoodMinigame;
 
-#[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone, Copy)]
-pub struct FoodSystemSet;
+const FOOD_SET: MinigameSet = MinigameSet(DayState::Evening);
 
 #[derive(Component)]
 pub struct Soup;
@@ -26,70 +27,121 @@ pub struct AddictionPeg(pub bool);
 #[derive(Component)]
 pub struct HardcoreAddictionPeg(pub bool);
 
+impl Minigame for FoodMinigame {
+	fn details(&self) -> MinigameDetails {
+		MinigameDetails {
+			state: DayState::Evening,
+			title: "Dinner - Evening",
+			tutorial: "backgrounds/food.png",
+			background: |_| "backgrounds/cart.png".to_string(),
+			music: "audio/bgm/Le_Pique_Nique_Martien.ogg",
+			high_music: "audio/bgm/Mars_Attaque.ogg",
+			targets: TargetDetails {
+				amount: 1,
+				width: 300.0,
+				height: 25.0,
+				spacing: 500.0,
+				speed: 100.0,
+				top_sprite: "sprites/soup.png",
+				bottom_sprite: "sprites/bowl.png",
+				decorate: fill_bowl,
+			},
+			drop_type: || Some(DropType::Mouth),
+			peg_speed: Vec2::ONE,
+		}
+	}
+}
+
 impl Plugin for FoodMinigame {
 	fn build(&self, app: &mut App) {
 		app
-		.configure_set(FoodSystemSet
-			.run_if(in_state(DayState::Evening))
-			.run_if(in_state(PauseState::Unpaused))
-			.run_if(in_state(GameState::Game)))
+		.add_plugin(RecipesPlugin)
 		.add_system(eat_food
 			.run_if(on_event::<BallCollisionEvent>())
-			.in_set(FoodSystemSet)
-			.after(PachinkoSystemSet))
-		.add_system(increase_addiction
-			.run_if(on_event::<BallCollisionEvent>())
-			.in_set(FoodSystemSet)
+			.in_set(FOOD_SET)
 			.after(PachinkoSystemSet))
 		.add_system(handle_mouth_soup
 			.run_if(on_event::<BallTargetHit>())
-			.in_set(FoodSystemSet)
+			.in_set(FOOD_SET)
 			.in_set(PachinkoSystemSet))
 		.add_system(move_targets
-			.in_set(FoodSystemSet)
+			.in_set(FOOD_SET)
 			.before(PachinkoSystemSet))
 		.add_system(move_addiction_pegs
-			.in_set(FoodSystemSet)
+			.in_set(FOOD_SET)
 			.before(PachinkoSystemSet))
 		.add_system(move_hardcore_addiction_pegs
-			.in_set(FoodSystemSet)
+			.in_set(FOOD_SET)
 			.before(PachinkoSystemSet))
 		.add_system(spawn_food_pegs.in_schedule(OnEnter(DayState::Evening)))
+		.add_system(capture_food_pegs
+			.in_set(AutosaveSet::Capture)
+			.run_if(in_state(DayState::Evening)))
+		.add_system(restore_food_pegs
+			.in_set(AutosaveSet::Restore)
+			.run_if(in_state(DayState::Evening)))
 		;
 	}
 }
 
+fn fill_bowl(
+	commands: &mut Commands,
+	_asset_server: &Res<AssetServer>,
+	pair: &TargetPair,
+) {
+	commands.entity(pair.top).insert((Soup, Target {radius: 0.5 * pair.width}));
+	commands.entity(pair.bottom).insert((Bowl, Soup));
+	spawn_soup_items(commands, pair.top);
+}
+
 fn eat_food(
 	mut commands: Commands,
 	mut collision_events: EventReader<BallCollisionEvent>,
 	mut user_data: ResMut<UserData>,
+	mut mouthful_query: Query<&mut Mouthful>,
+	mut sfx: EventWriter<PlaySfx>,
+	mut bursts: EventWriter<ParticleBurst>,
 ) {
 	let mut peg_to_despawn = Vec::new();
+	let mut eaten: Vec<(Entity, Entity, DropType)> = Vec::new();
 	for collision in collision_events.iter() {
 		match collision.collision_type {
 			BallCollisionEventType::Ball => (),
-			BallCollisionEventType::Peg{peg_type: ItemPeg(drop_type), peg, ..} => {
-				match drop_type.get_type() {
-					DropCategory::Drug => {
-						user_data.royal = (user_data.royal + 5.0).clamp(0.0, 100.0);
-						user_data.stress = (user_data.stress + 5.0).clamp(0.0, 100.0);
-						peg_to_despawn.push(peg);
-					},
-					_ => {
-						if drop_type.is_edible(){
-							user_data.stress = (user_data.stress - 1.0).clamp(0.0, 100.0);
-							peg_to_despawn.push(peg);
-						} else {
-							user_data.stress = (user_data.stress + 5.0).clamp(0.0, 100.0);
-						}
-					},
-				}},
+			BallCollisionEventType::Peg{peg_type: ItemPeg(drop_type), peg, ball, ..} => {
+				let info = drop_type.info();
+				info.eat.apply(&mut user_data);
+				if info.edible {
+					peg_to_despawn.push(peg);
+					sfx.send(PlaySfx::at(Sfx::Eat, collision.pos));
+					bursts.send(ParticleBurst {emitter: Emitter::Eat, pos: collision.pos});
+					// Drugs don't go in the soup
+					if info.category != DropCategory::Drug {
+						eaten.push((ball, peg, drop_type));
+					}
+				}
+			},
 			BallCollisionEventType::Peg{peg_type: PachinkoPeg, ..} => (),
 		}
 	}
 	//println!("{:?}", peg_to_despawn);
 	peg_to_despawn.sort();
 	peg_to_despawn.dedup();
+	// Each peg is only eaten once, even when it was hit twice this frame
+	eaten.sort_by_key(|(_, peg, _)| *peg);
+	eaten.dedup_by_key(|(_, peg, _)| *peg);
+	let mut new_mouthfuls: Vec<(Entity, Mouthful)> = Vec::new();
+	for (ball, _, drop_type) in eaten {
+		if let Ok(mut mouthful) = mouthful_query.get_mut(ball) {
+			mouthful.0.push(drop_type);
+		} else if let Some((_, mouthful)) = new_mouthfuls.iter_mut().find(|(entity, _)| *entity == ball) {
+			mouthful.0.push(drop_type);
+		} else {
+			new_mouthfuls.push((ball, Mouthful(vec![drop_type])));
+		}
+	}
+	for (ball, mouthful) in new_mouthfuls {
+		commands.entity(ball).insert(mouthful);
+	}
 	for peg in peg_to_despawn {
 		//println!("{:?}", peg);
 		commands.entity(peg).despawn_recursive();
@@ -100,11 +152,21 @@ fn handle_mouth_soup(
 	mut collision_events: EventReader<BallTargetHit>,
 	mut user_data: ResMut<UserData>,
 	soup_query: Query<&Transform, (With<Soup>, Without<Bowl>)>,
+	mouthful_query: Query<&Mouthful>,
+	mut pot: ResMut<SoupPot>,
+	book: Res<RecipeBook>,
+	mut sfx: EventWriter<PlaySfx>,
 ) {
 	for ball_event in collision_events.iter() {
 		let target_entity = ball_event.target;
-		if let Ok(_) = soup_query.get(target_entity) {
+		if let Ok(soup_transform) = soup_query.get(target_entity) {
+			sfx.send(PlaySfx::at(Sfx::Soup, soup_transform.translation.truncate()));
 			user_data.stress = (user_data.stress - 10.0).clamp(0.0, 100.0);
+			let mouthful = mouthful_query.get(ball_event.ball).map(|mouthful| mouthful.0.as_slice()).unwrap_or(&[]);
+			if let Some(recipe) = pot.serve(mouthful, &book) {
+				user_data.stress = (user_data.stress - recipe.relief).clamp(0.0, 100.0);
+				user_data.money = (user_data.money + recipe.money).clamp(0.0, 100.0);
+			}
 		}
 	}
 }
@@ -125,7 +187,12 @@ fn move_targets(
 fn spawn_food_pegs(
 	mut commands: Commands,
 	asset_server: Res<AssetServer>,
+	user_data: Res<UserData>,
 ) {
+	let mut rng = rand::thread_rng();
+	// Every item peg is indexed so eaten ones can be found again when restoring an autosave
+	let mut index = 0;
+
 	// Edible Pegs
 	let mut map: Vec<(f32, f32)> = vec![];
 	// Layered Walls
@@ -138,36 +205,14 @@ fn spawn_food_pegs(
 	}
 
 	for loc in map {
-		let peg = random_non_drug_edible();
-		let peg_texture_handle = asset_server.load(peg.get_path());
-		commands.spawn(
-			(
-				SpriteBundle {
-					transform: Transform::from_xyz(loc.0, loc.1, PEG_DEPTH),
-					texture: peg_texture_handle.clone(),
-					..default()
-				},
-				Peg(PegType::ItemPeg(peg)),
-				ClearOnDayTransition,
-			)
-		);
+		let peg = MEALS.draw(user_data.day, &mut rng);
+		spawn_food_peg(&mut commands, &asset_server, peg, Vec2::new(loc.0, loc.1), index);
+		index += 1;
 	}
 
 	// Drug Peg
-	let peg = DropType::Pill;
-	let peg_texture_handle = asset_server.load(peg.get_path());
-		commands.spawn(
-			(
-				SpriteBundle {
-					transform: Transform::from_xyz(512.0, 336.0, PEG_DEPTH)
-					.with_scale(Vec3::new(1.25, 1.25, PEG_DEPTH)),
-					texture: peg_texture_handle.clone(),
-					..default()
-				},
-				Peg(PegType::ItemPeg(peg)),
-				ClearOnDayTransition,
-			)
-		);
+	spawn_food_peg(&mut commands, &asset_server, DropType::Pill, CENTRE_PILL, index);
+	index += 1;
 
 	// Addiction Pegs
 	// Weed weed
@@ -192,19 +237,8 @@ fn spawn_food_pegs(
 
 	for loc in map {
 		let peg = DropType::Weed;
-		let peg_texture_handle = asset_server.load(peg.get_path());
-		commands.spawn(
-			(
-				SpriteBundle {
-					transform: Transform::from_xyz(loc.0 - 700.0, loc.1, PEG_DEPTH),
-					texture: peg_texture_handle.clone(),
-					..default()
-				},
-				Peg(PegType::ItemPeg(peg)),
-				ClearOnDayTransition,
-				AddictionPeg(true)
-			)
-		);
+		spawn_food_peg(&mut commands, &asset_server, peg, Vec2::new(loc.0 - 700.0, loc.1), index);
+		index += 1;
 	}
 
 	// Hardcore Addiction Pegs
@@ -234,19 +268,8 @@ fn spawn_food_pegs(
 
 	for loc in map {
 		let peg = DropType::Mushroom;
-		let peg_texture_handle = asset_server.load(peg.get_path());
-		commands.spawn(
-			(
-				SpriteBundle {
-					transform: Transform::from_xyz(loc.0, loc.1- 700.0, PEG_DEPTH),
-					texture: peg_texture_handle.clone(),
-					..default()
-				},
-				Peg(PegType::ItemPeg(peg)),
-				ClearOnDayTransition,
-				HardcoreAddictionPeg(true)
-			)
-		);
+		spawn_food_peg(&mut commands, &asset_server, peg, Vec2::new(loc.0, loc.1 - 700.0), index);
+		index += 1;
 	}
 
 	// Hardcore Addiction Pegs
@@ -267,19 +290,8 @@ fn spawn_food_pegs(
 
 	for loc in map {
 		let peg = DropType::Shot;
-		let peg_texture_handle = asset_server.load(peg.get_path());
-		commands.spawn(
-			(
-				SpriteBundle {
-					transform: Transform::from_xyz(loc.0, loc.1 - 700.0, PEG_DEPTH),
-					texture: peg_texture_handle.clone(),
-					..default()
-				},
-				Peg(PegType::ItemPeg(peg)),
-				ClearOnDayTransition,
-				HardcoreAddictionPeg(true)
-			)
-		);
+		spawn_food_peg(&mut commands, &asset_server, peg, Vec2::new(loc.0, loc.1 - 700.0), index);
+		index += 1;
 	}
 
 	// Hardcore Addiction Pegs
@@ -301,19 +313,8 @@ fn spawn_food_pegs(
 
 	for loc in map {
 		let peg = DropType::Pill;
-		let peg_texture_handle = asset_server.load(peg.get_path());
-		commands.spawn(
-			(
-				SpriteBundle {
-					transform: Transform::from_xyz(loc.0, loc.1 - 700.0, PEG_DEPTH),
-					texture: peg_texture_handle.clone(),
-					..default()
-				},
-				Peg(PegType::ItemPeg(peg)),
-				ClearOnDayTransition,
-				HardcoreAddictionPeg(true)
-			)
-		);
+		spawn_food_peg(&mut commands, &asset_server, peg, Vec2::new(loc.0, loc.1 - 700.0), index);
+		index += 1;
 	}
 
 	// Inedible Pegs
@@ -328,43 +329,81 @@ fn spawn_food_pegs(
 	}
 
 	for loc in map {
-		let peg = random_inedible_except_special();
-		let peg_texture_handle = asset_server.load(peg.get_path());
-		commands.spawn(
-			(
-				SpriteBundle {
-					transform: Transform::from_xyz(loc.0, loc.1, PEG_DEPTH),
-					texture: peg_texture_handle.clone(),
-					..default()
-				},
-				Peg(PegType::ItemPeg(peg)),
-				ClearOnDayTransition,
-				DrugPeg(loc.1, true)
-			)
-		);
+		let peg = JUNK.draw(user_data.day, &mut rng);
+		spawn_food_peg(&mut commands, &asset_server, peg, Vec2::new(loc.0, loc.1), index);
+		index += 1;
 	}
 }
 
-fn increase_addiction(
-	mut collision_events: EventReader<BallCollisionEvent>,
+fn capture_food_pegs(
+	peg_query: Query<(&BoardIndex, &Peg, &Transform)>,
 	mut user_data: ResMut<UserData>,
 ) {
-	for collision in collision_events.iter() {
-		match collision.collision_type {
-			BallCollisionEventType::Ball => (),
-			BallCollisionEventType::Peg{peg_type: ItemPeg(drop_type), ..} => {
-				if drop_type == DropType::Pill 
-				|| drop_type == DropType::Mushroom 
-				|| drop_type == DropType::Shot 
-				|| drop_type == DropType::Weed {
-					user_data.drugs_taken += 1.0;
-				}
-			},
-			BallCollisionEventType::Peg{..} => (),
+	if let Some(level) = user_data.level.as_mut() {
+		for (board_index, peg, transform) in peg_query.iter() {
+			if let ItemPeg(drop_type) = peg.0 {
+				level.food_pegs.push(SavedPeg {
+					index: board_index.0,
+					drop_type,
+					x: transform.translation.x,
+					y: transform.translation.y,
+				});
+			}
 		}
 	}
 }
 
+// Where the big pill sits in the middle of the board, every other pill is part of the mushroom
+const CENTRE_PILL: Vec2 = Vec2::new(512.0, 336.0);
+
+// Spawns an item peg with whichever movement its type gets on this board, also used to rebuild an autosaved board
+fn spawn_food_peg(
+	commands: &mut Commands,
+	asset_server: &AssetServer,
+	drop_type: DropType,
+	pos: Vec2,
+	index: usize,
+) {
+	let mut transform = Transform::from_xyz(pos.x, pos.y, PEG_DEPTH);
+	if drop_type == DropType::Pill && pos == CENTRE_PILL {
+		transform = transform.with_scale(Vec3::new(1.25, 1.25, PEG_DEPTH));
+	}
+	let mut peg = commands.spawn(
+		(
+			SpriteBundle {
+				transform,
+				texture: asset_server.load(drop_type.get_path()),
+				..default()
+			},
+			Peg(PegType::ItemPeg(drop_type)),
+			BoardIndex(index),
+			ClearOnDayTransition,
+		)
+	);
+	match drop_type {
+		DropType::Pill if pos == CENTRE_PILL => (),
+		DropType::Weed => {peg.insert(AddictionPeg(true));},
+		DropType::Mushroom | DropType::Shot | DropType::Pill => {peg.insert(HardcoreAddictionPeg(true));},
+		_ if !drop_type.info().edible => {peg.insert(DrugPeg(pos.y, true));},
+		_ => (),
+	}
+}
+
+fn restore_food_pegs(
+	mut commands: Commands,
+	peg_query: Query<Entity, (With<Peg>, With<BoardIndex>)>,
+	restore: Res<RestoreLevel>,
+	asset_server: Res<AssetServer>,
+) {
+	for entity in peg_query.iter() {
+		commands.entity(entity).despawn_recursive();
+	}
+	// Pegs eaten before the save aren't in it
+	for saved in restore.0.food_pegs.iter() {
+		spawn_food_peg(&mut commands, &asset_server, saved.drop_type, Vec2::new(saved.x, saved.y), saved.index);
+	}
+}
+
 fn move_addiction_pegs(
 	mut addiction_peg_query: Query<(&mut Transform, &mut AddictionPeg)>,
 	user_data: Res<UserData>,
@@ -1,5 +1,162 @@
+// Every day activity is a Minigame, registered with add_minigame
+// Registering one hooks up everything activities share: targets, drop zone, background, music, stat clocks and cleanup
+// The minigame's own plugin then only adds its board and scoring
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::setup::despawn_entities_with;
+
+use super::{
+	PachinkoSystemSet,
+	states::{DayState, GameState, PauseState, initialise_clock, initialise_wallet, initialise_stress, initialise_royal},
+	targets::{spawn_targets, TargetDetails, ClearOnDayTransition},
+	balls::{spawn_drop_zone, Ball, DropSprite},
+	backgrounds::spawn_background,
+	calendar::DaySchedule,
+	food::DropType,
+};
+
 pub mod parlor;
 pub mod food;
 pub mod work;
+pub mod slots;
+pub mod fever;
+pub mod orders;
+pub mod recipes;
+
+pub use self::{food::FoodMinigame, parlor::ParlorMinigame, work::WorkMinigame};
+
+pub const HORIZONTAL_SPEED: f32 = 30.0;
+pub const VERTICAL_SPEED: f32 = 12.0;
+
+pub struct MinigamesPlugin;
+
+impl Plugin for MinigamesPlugin {
+	fn build(&self, app: &mut App) {
+		app
+		.init_resource::<Minigames>()
+		.add_systems((move_pegs_horizontal, move_pegs_vertical).in_set(PachinkoSystemSet))
+		;
+	}
+}
+
+// Everything shared code needs to know about an activity
+pub trait Minigame: Plugin {
+	fn details(&self) -> MinigameDetails;
+}
+
+pub struct MinigameDetails {
+	// Time slot the activity is played in
+	pub state: DayState,
+	// Shown on the popup before the level starts
+	pub title: &'static str,
+	pub tutorial: &'static str,
+	pub background: fn(&DaySchedule) -> String,
+	// Played while sober and while royal is above half
+	pub music: &'static str,
+	pub high_music: &'static str,
+	pub targets: TargetDetails,
+	// What the player drops from the drop zone, None when the minigame hands out its own balls
+	pub drop_type: fn() -> Option<DropType>,
+	// Multiplies HORIZONTAL_SPEED and VERTICAL_SPEED for moving pegs
+	pub peg_speed: Vec2,
+}
+
+#[derive(Resource, Default)]
+pub struct Minigames(HashMap<DayState, MinigameDetails>);
+
+impl Minigames {
+	pub fn get(&self, state: DayState) -> Option<&MinigameDetails> {
+		self.0.get(&state)
+	}
+}
+
+// Runs while a minigame is being played
+#[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone, Copy)]
+pub struct MinigameSet(pub DayState);
+
+pub trait AddMinigame {
+	fn add_minigame<M: Minigame>(&mut self, minigame: M) -> &mut Self;
+}
+
+impl AddMinigame for App {
+	fn add_minigame<M: Minigame>(&mut self, minigame: M) -> &mut Self {
+		let details = minigame.details();
+		let state = details.state;
+		self.init_resource::<Minigames>();
+		self.world.resource_mut::<Minigames>().0.insert(state, details);
+		let clean_up = (
+			despawn_entities_with::<ClearOnDayTransition>,
+			despawn_entities_with::<Ball>,
+			despawn_entities_with::<DropSprite>,
+		);
+		self
+		.configure_set(MinigameSet(state)
+			.run_if(in_state(state))
+			.run_if(in_state(PauseState::Unpaused))
+			.run_if(in_state(GameState::Game)))
+		.add_systems((
+				initialise_clock,
+				initialise_wallet,
+				initialise_stress,
+				initialise_royal,
+				spawn_targets,
+				spawn_drop_zone,
+				spawn_background,
+			).in_schedule(OnEnter(state)))
+		.add_systems(clean_up.in_schedule(OnExit(state)))
+		.add_plugin(minigame)
+	}
+}
+
+// Pegs used in most boards, a flat triangle hanging down from its top peg
+pub fn shallow_triangle(
+	top_x: f32,
+	top_y: f32,
+) -> Vec<(f32, f32)> {
+	vec![(top_x, top_y),(top_x-25.0,top_y-20.0),(top_x-50.0,top_y-40.0),(top_x+25.0,top_y-20.0),(top_x+50.0,top_y-40.0)]
+}
+
+#[derive(Component)]
+pub struct MoveHorizontalPeg(pub f32, pub bool);
+
+#[derive(Component)]
+pub struct MoveVerticalPeg(pub f32, pub bool);
+
+fn move_pegs_horizontal(
+	mut horizontal_peg_query: Query<(&mut Transform, &mut MoveHorizontalPeg)>,
+	minigames: Res<Minigames>,
+	state: Res<State<DayState>>,
+	time: Res<Time>,
+) {
+	let Some(minigame) = minigames.get(state.0) else {
+		return;
+	};
+	let speed = HORIZONTAL_SPEED * minigame.peg_speed.x;
+	for (mut transform, mut move_peg) in horizontal_peg_query.iter_mut() {
+		if move_peg.1 {transform.translation.x += speed * time.delta_seconds()
+			} else {transform.translation.x += -speed * time.delta_seconds()};
+		if transform.translation.x >= move_peg.0 + 125.0 {move_peg.1 = false};
+		if transform.translation.x <= move_peg.0 - 125.0 {move_peg.1 = true};
+	}
+}
 
-pub use self::{food::FoodMinigame, parlor::ParlorMinigame, work::WorkMinigame};
\ No newline at end of file
+fn move_pegs_vertical(
+	mut vertical_peg_query: Query<(&mut Transform, &mut MoveVerticalPeg)>,
+	minigames: Res<Minigames>,
+	state: Res<State<DayState>>,
+	time: Res<Time>,
+) {
+	let Some(minigame) = minigames.get(state.0) else {
+		return;
+	};
+	let speed = VERTICAL_SPEED * minigame.peg_speed.y;
+	for (mut transform, mut move_peg) in vertical_peg_query.iter_mut() {
+		if move_peg.1 {transform.translation.y += speed * time.delta_seconds()
+			} else {transform.translation.y += -speed * time.delta_seconds()};
+		if transform.translation.y >= move_peg.0 + 120.0 {move_peg.1 = false};
+		if transform.translation.y <= move_peg.0 {move_peg.1 = true};
+	}
+}
@@ -0,0 +1,336 @@
+// Customer orders for the work minigame
+// Customers queue up wanting a few items from the baskets, and leave if they wait too long
+// Finished orders pay more the faster and cleaner they were filled, and correct fills in a row build a combo
+
+use bevy::prelude::*;
+use rand::{Rng, seq::SliceRandom};
+use serde::{Serialize, Deserialize};
+
+use crate::{
+	game::{food::DropType, states::{DayState, FRAME_FRONT_DEPTH, FRAME_INNER_DEPTH}, targets::{BasketLabel, ClearOnDayTransition}},
+	menu::styles::get_vn_text_style,
+	save::autosave::{AutosaveSet, RestoreLevel},
+	setup::UserData,
+};
+
+use super::MinigameSet;
+
+pub const MAX_ORDERS: usize = 3;
+pub const MIN_ORDER_SIZE: usize = 2;
+pub const MAX_ORDER_SIZE: usize = 4;
+// Seconds a customer waits, plus some more for every item they want
+const BASE_PATIENCE: f32 = 10.0;
+const ITEM_PATIENCE: f32 = 6.0;
+const NEW_CUSTOMER_TIME: f32 = 5.0;
+// Money for each item of a finished order, before speed, accuracy and combo
+const ITEM_PAY: f32 = 4.0;
+// Paid for every ball in the right basket, finished order or not
+const FILL_PAY: f32 = 1.0;
+const COMBO_STEP: f32 = 0.25;
+const MAX_COMBO_MULTIPLIER: f32 = 3.0;
+pub const WRONG_BASKET_STRESS: f32 = 5.0;
+const WALKOUT_STRESS: f32 = 10.0;
+
+// Where the queue is drawn, in the column next to the board
+const QUEUE_POSITION: Vec2 = Vec2::new(1080.0, 760.0);
+const ROW_SPACING: f32 = 80.0;
+const ITEM_SPACING: f32 = 48.0;
+const PATIENCE_BAR_WIDTH: f32 = 200.0;
+
+pub struct OrdersPlugin;
+
+impl Plugin for OrdersPlugin {
+	fn build(&self, app: &mut App) {
+		app
+		.init_resource::<OrderQueue>()
+		.add_systems((reset_orders, spawn_order_display).in_schedule(OnEnter(DayState::Morning)))
+		.add_system(serve_customers.in_set(MinigameSet(DayState::Morning)))
+		.add_system(update_order_display
+			.in_set(MinigameSet(DayState::Morning))
+			.after(serve_customers))
+		.add_system(capture_orders
+			.in_set(AutosaveSet::Capture)
+			.run_if(in_state(DayState::Morning)))
+		.add_system(restore_orders
+			.in_set(AutosaveSet::Restore)
+			.run_if(in_state(DayState::Morning)))
+		;
+	}
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Order {
+	pub items: Vec<DropType>,
+	pub filled: Vec<bool>,
+	// Seconds until the customer walks out, and how many of them have passed
+	pub patience: f32,
+	pub waited: f32,
+	// Balls dropped into the wrong basket while this was the first order
+	pub mistakes: u32,
+}
+
+impl Order {
+	// Items are picked from what the baskets are labelled with
+	pub fn new<R: Rng>(menu: &[DropType], rng: &mut R) -> Self {
+		let size = rng.gen_range(MIN_ORDER_SIZE..=MAX_ORDER_SIZE);
+		let items: Vec<DropType> = (0..size).filter_map(|_| menu.choose(rng).copied()).collect();
+		Self {
+			filled: vec![false; items.len()],
+			patience: BASE_PATIENCE + ITEM_PATIENCE * items.len() as f32,
+			waited: 0.0,
+			mistakes: 0,
+			items,
+		}
+	}
+
+	// First unfilled slot for this item
+	pub fn wants(&self, drop_type: DropType) -> Option<usize> {
+		self.items.iter().zip(self.filled.iter())
+			.position(|(item, filled)| *item == drop_type && !filled)
+	}
+
+	pub fn complete(&self) -> bool {
+		self.filled.iter().all(|filled| *filled)
+	}
+
+	// Fraction of the patience left, from 1 down to 0
+	pub fn patience_left(&self) -> f32 {
+		(1.0 - self.waited / self.patience).clamp(0.0, 1.0)
+	}
+
+	pub fn pay(&self, combo: u32) -> f32 {
+		let speed = 0.5 + 0.5 * self.patience_left();
+		let accuracy = 1.0 / (1.0 + self.mistakes as f32);
+		ITEM_PAY * self.items.len() as f32 * speed * accuracy * combo_multiplier(combo)
+	}
+}
+
+// What a finished order's pay is multiplied by after this many correct fills in a row
+fn combo_multiplier(combo: u32) -> f32 {
+	(1.0 + COMBO_STEP * combo as f32).min(MAX_COMBO_MULTIPLIER)
+}
+
+#[derive(Resource, Default)]
+pub struct OrderQueue {
+	// Oldest first
+	pub orders: Vec<Order>,
+	// Correct fills in a row
+	pub combo: u32,
+	// Seconds until the next customer turns up
+	pub next_customer: f32,
+}
+
+impl OrderQueue {
+	// Fills the oldest order wanting this item, returns the money earned,
+	// a little for the ball and the order's pay on top if that finished it
+	pub fn fill(&mut self, drop_type: DropType) -> f32 {
+		let Some((index, slot)) = self.orders.iter().enumerate()
+			.find_map(|(index, order)| order.wants(drop_type).map(|slot| (index, slot))) else {
+			return FILL_PAY;
+		};
+		self.combo += 1;
+		self.orders[index].filled[slot] = true;
+		if self.orders[index].complete() {
+			FILL_PAY + self.orders.remove(index).pay(self.combo)
+		} else {
+			FILL_PAY
+		}
+	}
+
+	// A ball went into the wrong basket
+	pub fn miss(&mut self) {
+		self.combo = 0;
+		if let Some(order) = self.orders.first_mut() {
+			order.mistakes += 1;
+		}
+	}
+
+	// Every item still waiting to be filled, repeats included
+	pub fn wanted(&self) -> Vec<DropType> {
+		self.orders.iter()
+			.flat_map(|order| order.items.iter().zip(order.filled.iter()))
+			.filter(|(_, filled)| !**filled)
+			.map(|(item, _)| *item)
+			.collect()
+	}
+}
+
+// One item of one order in the queue display
+#[derive(Component)]
+struct OrderItem {
+	order: usize,
+	item: usize,
+}
+
+#[derive(Component)]
+struct PatienceBar(usize);
+
+#[derive(Component)]
+struct ComboText;
+
+fn reset_orders(
+	mut queue: ResMut<OrderQueue>,
+) {
+	*queue = OrderQueue::default();
+}
+
+fn spawn_order_display(
+	mut commands: Commands,
+	asset_server: Res<AssetServer>,
+) {
+	for order in 0..MAX_ORDERS {
+		let y = QUEUE_POSITION.y - order as f32 * ROW_SPACING;
+		for item in 0..MAX_ORDER_SIZE {
+			commands.spawn(
+				(
+					SpriteBundle {
+						transform: Transform::from_xyz(QUEUE_POSITION.x + item as f32 * ITEM_SPACING, y, FRAME_FRONT_DEPTH),
+						visibility: Visibility::Hidden,
+						..default()
+					},
+					OrderItem { order, item },
+					ClearOnDayTransition,
+				)
+			);
+		}
+		commands.spawn(
+			(
+				SpriteBundle {
+					transform: Transform::from_xyz(QUEUE_POSITION.x, y - 32.0, FRAME_INNER_DEPTH)
+						.with_scale(Vec3::new(0.0, 0.25, 1.0)),
+					texture: asset_server.load("sprites/clock.png"),
+					visibility: Visibility::Hidden,
+					..default()
+				},
+				PatienceBar(order),
+				ClearOnDayTransition,
+			)
+		);
+	}
+	commands.spawn(
+		(
+			Text2dBundle {
+				text: Text::from_section("", get_vn_text_style(&asset_server)),
+				transform: Transform::from_xyz(QUEUE_POSITION.x + 360.0, QUEUE_POSITION.y, FRAME_FRONT_DEPTH),
+				..default()
+			},
+			ComboText,
+			ClearOnDayTransition,
+		)
+	);
+}
+
+fn serve_customers(
+	mut queue: ResMut<OrderQueue>,
+	label_query: Query<&DropType, With<BasketLabel>>,
+	mut user_data: ResMut<UserData>,
+	time: Res<Time>,
+) {
+	let delta = time.delta_seconds();
+	let before = queue.orders.len();
+	for order in queue.orders.iter_mut() {
+		order.waited += delta;
+	}
+	queue.orders.retain(|order| order.waited < order.patience);
+	let walkouts = before - queue.orders.len();
+	if walkouts > 0 {
+		queue.combo = 0;
+		user_data.stress = (user_data.stress + WALKOUT_STRESS * walkouts as f32).clamp(0.0, 100.0);
+	}
+
+	queue.next_customer -= delta;
+	if queue.next_customer <= 0.0 && queue.orders.len() < MAX_ORDERS {
+		let menu: Vec<DropType> = label_query.iter().copied().collect();
+		if !menu.is_empty() {
+			let order = Order::new(&menu, &mut rand::thread_rng());
+			queue.orders.push(order);
+			queue.next_customer = NEW_CUSTOMER_TIME;
+		}
+	}
+}
+
+fn update_order_display(
+	queue: Res<OrderQueue>,
+	asset_server: Res<AssetServer>,
+	mut item_query: Query<(&OrderItem, &mut Handle<Image>, &mut Sprite, &mut Visibility), Without<PatienceBar>>,
+	mut bar_query: Query<(&PatienceBar, &mut Transform, &mut Visibility), Without<OrderItem>>,
+	mut combo_query: Query<&mut Text, With<ComboText>>,
+) {
+	for (slot, mut texture, mut sprite, mut visibility) in item_query.iter_mut() {
+		let item = queue.orders.get(slot.order)
+			.and_then(|order| order.items.get(slot.item).zip(order.filled.get(slot.item)));
+		match item {
+			Some((drop_type, filled)) => {
+				*visibility = Visibility::Visible;
+				*texture = asset_server.load(drop_type.get_path());
+				// Filled items are greyed out
+				sprite.color = if *filled {Color::rgba(0.4, 0.4, 0.4, 0.6)} else {Color::WHITE};
+			},
+			None => *visibility = Visibility::Hidden,
+		}
+	}
+	for (bar, mut transform, mut visibility) in bar_query.iter_mut() {
+		match queue.orders.get(bar.0) {
+			Some(order) => {
+				*visibility = Visibility::Visible;
+				let width = order.patience_left() * PATIENCE_BAR_WIDTH;
+				transform.scale.x = width / 32.0;
+				transform.translation.x = QUEUE_POSITION.x - 16.0 + width / 2.0;
+			},
+			None => *visibility = Visibility::Hidden,
+		}
+	}
+	for mut text in combo_query.iter_mut() {
+		text.sections[0].value = if queue.combo > 1 {format!("Combo x{:.2}", combo_multiplier(queue.combo))} else {String::new()};
+	}
+}
+
+fn capture_orders(
+	queue: Res<OrderQueue>,
+	mut user_data: ResMut<UserData>,
+) {
+	if let Some(level) = user_data.level.as_mut() {
+		level.orders = queue.orders.clone();
+		level.combo = queue.combo;
+	}
+}
+
+fn restore_orders(
+	mut queue: ResMut<OrderQueue>,
+	restore: Res<RestoreLevel>,
+) {
+	queue.orders = restore.0.orders.clone();
+	queue.combo = restore.0.combo;
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn order(items: Vec<DropType>) -> Order {
+		Order {
+			filled: vec![false; items.len()],
+			patience: 10.0,
+			waited: 0.0,
+			mistakes: 0,
+			items,
+		}
+	}
+
+	#[test]
+	fn every_fill_pays_and_finishing_pays_more() {
+		let mut queue = OrderQueue {orders: vec![order(vec![DropType::Apple, DropType::Cheese])], ..default()};
+		// Nobody wants it, still the right basket
+		assert_eq!(queue.fill(DropType::Bagel), FILL_PAY);
+		assert_eq!(queue.fill(DropType::Apple), FILL_PAY);
+		assert!(queue.fill(DropType::Cheese) > FILL_PAY);
+		assert!(queue.orders.is_empty());
+	}
+
+	#[test]
+	fn combos_stop_paying_more_at_the_cap() {
+		assert_eq!(combo_multiplier(0), 1.0);
+		assert_eq!(combo_multiplier(2), 1.0 + 2.0 * COMBO_STEP);
+		assert_eq!(combo_multiplier(100), MAX_COMBO_MULTIPLIER);
+	}
+}
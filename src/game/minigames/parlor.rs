@@ -2,24 +2,48 @@ use bevy::prelude::*;
 
 use crate::{
 	game::{
-		physics::{Velocity, BallTargetHit, BallCollisionEvent, BallCollisionEventType}, PachinkoSystemSet,
-		pegs::{Peg, PEG_DEPTH, PegType}, targets::ClearOnDayTransition, food::DropType,
-		states::{DayState, PauseState, GameState}, balls::{BALL_DEPTH, Ball}
-	}, setup::UserData
+		physics::{BallTargetHit, BallCollisionEvent, BallCollisionEventType}, PachinkoSystemSet,
+		pegs::{Peg, PEG_DEPTH, PegType, BoardIndex}, targets::{ClearOnDayTransition, Rim, Hole, Target, TargetDetails, TargetPair}, food::DropType,
+		states::{DayState, PauseState, GameState},
+	}, setup::UserData, save::autosave::{AutosaveSet, RestoreLevel}, audio::sfx::{PlaySfx, Sfx},
+	vfx::particles::{ParticleBurst, Emitter},
 };
 
-pub const PRIZE_TIME: f32 = 10.0;
-pub const PRIZE_LAUNCH_TIME: f32 = 0.5;
+use super::{VERTICAL_SPEED, MoveHorizontalPeg, MoveVerticalPeg, Minigame, MinigameDetails, shallow_triangle,
+	slots::{SlotsPlugin, StartPocket}, fever::{FeverPlugin, Fever, Jackpot}, MinigameSet};
 
-pub const HORIZONTAL_SPEED: f32 = 30.0;
-pub const VERTICAL_SPEED: f32 = 12.0;
 pub struct ParlorMinigame;
 
+impl Minigame for ParlorMinigame {
+	fn details(&self) -> MinigameDetails {
+		MinigameDetails {
+			state: DayState::Night,
+			title: "Arcade - Night",
+			tutorial: "backgrounds/parlor.png",
+			background: |day| format!("backgrounds/arcade{}.png", day.arcade),
+			music: "audio/bgm/parlour_sober_final.ogg",
+			high_music: "audio/bgm/parlour_sugar_rush.ogg",
+			targets: TargetDetails {
+				amount: 3,
+				width: 200.0,
+				height: 25.0,
+				spacing: 100.0,
+				speed: 0.0,
+				top_sprite: "sprites/rim.png",
+				bottom_sprite: "sprites/hole.png",
+				decorate: sink_holes,
+			},
+			drop_type: || Some(DropType::Ball),
+			peg_speed: Vec2::ONE,
+		}
+	}
+}
+
 impl Plugin for ParlorMinigame {
 	fn build(&self, app: &mut App) {
 		app
-		.insert_resource(PrizeTimer(Timer::from_seconds(PRIZE_TIME, TimerMode::Once)))
-		.insert_resource(PrizeLaunchTimer(Timer::from_seconds(PRIZE_LAUNCH_TIME, TimerMode::Repeating)))
+		.add_plugin(SlotsPlugin)
+		.add_plugin(FeverPlugin)
 		.add_system(handle_parlor_balls
 			.run_if(on_event::<BallTargetHit>())
 			.run_if(in_state(DayState::Night))			
@@ -30,49 +54,39 @@ impl Plugin for ParlorMinigame {
 			.in_set(PachinkoSystemSet))
 		.add_system(spawn_parlor_pegs.in_schedule(OnEnter(DayState::Night)))
 		.add_system(spawn_bells.in_schedule(OnEnter(DayState::Night)))
-		.add_system(spawn_prize
-			.run_if(in_state(DayState::Night))
-			.run_if(in_state(PauseState::Unpaused))
-			.run_if(in_state(GameState::Game)))
+		.add_system(ring_in_finale
+			.in_set(MinigameSet(DayState::Night))
+			.after(ring_bells))
 		.add_system(move_drug_pegs
 			.run_if(in_state(DayState::Night))
 			.run_if(in_state(PauseState::Unpaused))
 			.run_if(in_state(GameState::Game)))
-		.add_system(move_pegs_horizontal
-			.run_if(in_state(DayState::Night))
-			.run_if(in_state(PauseState::Unpaused))
-			.run_if(in_state(GameState::Game)))
-		.add_system(move_pegs_vertical
-			.run_if(in_state(DayState::Night))
-			.run_if(in_state(PauseState::Unpaused))
-			.run_if(in_state(GameState::Game)))
+		.add_system(capture_bells
+			.in_set(AutosaveSet::Capture)
+			.run_if(in_state(DayState::Night)))
+		.add_system(restore_bells
+			.in_set(AutosaveSet::Restore)
+			.run_if(in_state(DayState::Night)))
 		;
 	}
 }
 
-#[derive(Resource)]
-pub struct PrizeTimer(pub Timer);
-
-#[derive(Resource)]
-pub struct PrizeLaunchTimer(pub Timer);
-
 #[derive(Component)]
 pub struct DrugPeg(pub f32, pub bool);
 
-#[derive(Component)]
-pub struct MoveHorizontalPeg(pub f32, pub bool);
-
-#[derive(Component)]
-pub struct MoveVerticalPeg(pub f32, pub bool);
-
 #[derive(Component)]
 pub struct Bell(bool);
 
-fn shallow_triangle(
-	top_x: f32,
-	top_y: f32,
-) -> Vec<(f32, f32)> {
-	vec![(top_x, top_y),(top_x-25.0,top_y-20.0),(top_x-50.0,top_y-40.0),(top_x+25.0,top_y-20.0),(top_x+50.0,top_y-40.0)]
+fn sink_holes(
+	commands: &mut Commands,
+	_asset_server: &Res<AssetServer>,
+	pair: &TargetPair,
+) {
+	commands.entity(pair.top).insert((Rim, Target {radius: 0.5 * pair.width}));
+	commands.entity(pair.bottom).insert(Hole);
+	if pair.index == 1 {
+		commands.entity(pair.top).insert(StartPocket);
+	}
 }
 
 fn spawn_parlor_pegs(
@@ -220,7 +234,7 @@ fn spawn_bells(
 
 	let bell_texture_handle = asset_server.load("droppables/bell.png");
 
-	for loc in map {
+	for (index, loc) in map.into_iter().enumerate() {
 		commands.spawn(
 			(
 				SpriteBundle {
@@ -231,16 +245,45 @@ fn spawn_bells(
 				Peg(PegType::ItemPeg(DropType::Bell)),
 				ClearOnDayTransition,
 				Bell(false),
+				BoardIndex(index),
 			)
 		);
 	}
 }
 
+fn capture_bells(
+	bell_query: Query<(&BoardIndex, &Bell)>,
+	mut user_data: ResMut<UserData>,
+) {
+	if let Some(level) = user_data.level.as_mut() {
+		for (board_index, bell) in bell_query.iter() {
+			if bell.0 {
+				level.rung_bells.push(board_index.0);
+			}
+		}
+	}
+}
+
+fn restore_bells(
+	mut bell_query: Query<(&BoardIndex, &mut Bell, &mut bevy::asset::Handle<Image>)>,
+	restore: Res<RestoreLevel>,
+	asset_server: Res<AssetServer>,
+) {
+	for (board_index, mut bell, mut texture) in bell_query.iter_mut() {
+		if restore.0.rung_bells.contains(&board_index.0) {
+			bell.0 = true;
+			*texture = asset_server.load("droppables/crown.png");
+		}
+	}
+}
+
 fn ring_bells(
 	mut commands: Commands,
 	mut collision_events: EventReader<BallCollisionEvent>,
 	asset_server: Res<AssetServer>,
 	mut user_data: ResMut<UserData>,
+	mut sfx: EventWriter<PlaySfx>,
+	mut bursts: EventWriter<ParticleBurst>,
 ) {
 	for collision in collision_events.iter() {
 		match collision.collision_type {
@@ -253,6 +296,8 @@ fn ring_bells(
 					let sprite: bevy::asset::Handle<Image> = asset_server.load("droppables/crown.png");
 					commands.entity(peg).insert(sprite.clone());
 					user_data.money = (user_data.money + 2.0).clamp(0.0, 100.0);
+					sfx.send(PlaySfx::at(Sfx::Bell, collision.pos));
+					bursts.send(ParticleBurst {emitter: Emitter::Bell, pos: collision.pos});
 				}
 			},
 			BallCollisionEventType::Peg{..} => (),
@@ -260,82 +305,15 @@ fn ring_bells(
 	}
 }
 
-fn spawn_prize(
-	mut commands: Commands,
-	mut bell_query: Query<(Entity, &mut Bell)>,
-	time: Res<Time>,
-	mut prize_timer: ResMut<PrizeTimer>,
-	mut prize_launch_timer: ResMut<PrizeLaunchTimer>,
-	asset_server: Res<AssetServer>,
-	mut next_game_state: ResMut<NextState<GameState>>,
-	mut next_pause_state: ResMut<NextState<PauseState>>,
-	mut next_day_state: ResMut<NextState<DayState>>,
-) {
-	let mut total_bells_rang = 0;
-	for (_, bell) in bell_query.iter() {
-		if bell.0 {
-			total_bells_rang += 1;
-		}
-	}
-	if total_bells_rang == 6 {
-		prize_timer.0.tick(time.delta());
-		prize_launch_timer.0.tick(time.delta());
-		if prize_launch_timer.0.just_finished() {
-			let (x_rand,y_rand): (f32,f32) = rand::random();
-			let prize_chance = rand::random::<f32>();
-			let prize = if prize_chance > 0.3 {DropType::Diamond
-				} else if prize_chance > 0.6 {DropType::Crown
-				} else {DropType::Money};
-			commands.spawn(
-				(
-					SpriteBundle {
-						transform: Transform::from_xyz(1300.0, 700.0, BALL_DEPTH),
-						texture: asset_server.load(prize.get_path()),
-						..default()
-					},
-					Ball,
-					Velocity(Vec2::new(-(300.0 + x_rand * 150.0), 250.0 + y_rand * 50.0)),
-					prize,
-				)
-			);
-			if prize_timer.0.finished() {
-				for (entity, mut bell) in bell_query.iter_mut() {
-					bell.0 = false;
-					commands.entity(entity).remove::<bevy::asset::Handle<Image>>();
-					let sprite: bevy::asset::Handle<Image> = asset_server.load("droppables/bell.png");
-					commands.entity(entity).insert(sprite.clone());
-
-					next_day_state.0 = Some(DayState::Dawn);
-					next_pause_state.0 = Some(PauseState::Initial);
-					next_game_state.0 = Some(GameState::Outro);
-					prize_timer.0.reset();
-				}
-			}
-		}
-	}
-}
-
-fn move_pegs_horizontal(
-	mut horizontal_peg_query: Query<(&mut Transform, &mut MoveHorizontalPeg)>,
-	time: Res<Time>,
-) {
-	for (mut transform, mut move_peg) in horizontal_peg_query.iter_mut() {
-		if move_peg.1 {transform.translation.x += HORIZONTAL_SPEED * time.delta_seconds()
-			} else {transform.translation.x += -HORIZONTAL_SPEED * time.delta_seconds()};
-		if transform.translation.x >= move_peg.0 + 125.0 {move_peg.1 = false};
-		if transform.translation.x <= move_peg.0 - 125.0 {move_peg.1 = true};
-	}
-}
-
-fn move_pegs_vertical(
-	mut vertical_peg_query: Query<(&mut Transform, &mut MoveVerticalPeg)>,
-	time: Res<Time>,
+// Ringing every bell wins a last fever, the night and the game end once it's over
+fn ring_in_finale(
+	bell_query: Query<&Bell>,
+	mut fever: ResMut<Fever>,
+	mut jackpots: EventWriter<Jackpot>,
 ) {
-	for (mut transform, mut move_peg) in vertical_peg_query.iter_mut() {
-		if move_peg.1 {transform.translation.y += VERTICAL_SPEED * time.delta_seconds()
-			} else {transform.translation.y += -VERTICAL_SPEED * time.delta_seconds()};
-		if transform.translation.y >= move_peg.0 + 120.0 {move_peg.1 = false};
-		if transform.translation.y <= move_peg.0 {move_peg.1 = true};
+	if !fever.finale && bell_query.iter().filter(|bell| bell.0).count() == 6 {
+		fever.finale = true;
+		jackpots.send(Jackpot);
 	}
 }
 
@@ -0,0 +1,306 @@
+// Soup recipes for the food minigame
+// Mouths collect the ingredients they eat on the way down and tip them into the soup when they land in it,
+// a soup matching the recipe card pays out far more than a plain one
+
+use bevy::prelude::*;
+
+use crate::{
+	game::{
+		food::{DropCategory, DropType}, states::DayState,
+		targets::{ClearOnDayTransition, LABEL_TARGET_DEPTH, TOP_TARGET_DEPTH},
+	},
+	menu::styles::get_vn_text_style,
+	save::autosave::{AutosaveSet, RestoreLevel},
+	setup::UserData,
+};
+
+use super::MinigameSet;
+
+// Ingredients the soup holds, a full soup which doesn't match the card gets tipped out
+pub const SOUP_SIZE: usize = 6;
+const SOUP_ITEM_SPACING: f32 = 40.0;
+const RECIPE_CARD_POSITION: Vec2 = Vec2::new(1300.0, 760.0);
+
+pub struct RecipesPlugin;
+
+impl Plugin for RecipesPlugin {
+	fn build(&self, app: &mut App) {
+		app
+		.init_resource::<RecipeBook>()
+		.init_resource::<SoupPot>()
+		.add_systems((reset_soup, spawn_recipe_card).in_schedule(OnEnter(DayState::Evening)))
+		.add_system(update_soup_display.in_set(MinigameSet(DayState::Evening)))
+		.add_system(capture_soup
+			.in_set(AutosaveSet::Capture)
+			.run_if(in_state(DayState::Evening)))
+		.add_system(restore_soup
+			.in_set(AutosaveSet::Restore)
+			.run_if(in_state(DayState::Evening)))
+		;
+	}
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Ingredient {
+	Category(DropCategory),
+	Item(DropType),
+}
+
+impl Ingredient {
+	pub fn matches(&self, drop_type: DropType) -> bool {
+		match self {
+			Ingredient::Category(category) => drop_type.get_type() == *category,
+			Ingredient::Item(item) => drop_type == *item,
+		}
+	}
+}
+
+#[derive(Clone, Debug)]
+pub struct Recipe {
+	pub name: &'static str,
+	pub ingredients: Vec<(u8, Ingredient)>,
+	// Stress taken off and money paid on top of a plain soup
+	pub relief: f32,
+	pub money: f32,
+}
+
+impl Recipe {
+	// Every ingredient is found in the soup, each item in the soup counts once
+	pub fn matches(&self, soup: &[DropType]) -> bool {
+		let mut left: Vec<DropType> = soup.to_vec();
+		// Exact items first so they aren't used up by a category
+		let mut ingredients = self.ingredients.clone();
+		ingredients.sort_by_key(|(_, ingredient)| matches!(ingredient, Ingredient::Category(_)));
+		for (amount, ingredient) in ingredients {
+			for _ in 0..amount {
+				match left.iter().position(|drop_type| ingredient.matches(*drop_type)) {
+					Some(index) => {left.swap_remove(index);},
+					None => return false,
+				}
+			}
+		}
+		true
+	}
+
+	// Written the way it's shown on the card, like "2 Veg + 1 Produce + Salt"
+	pub fn card(&self) -> String {
+		self.ingredients.iter().map(|(amount, ingredient)| match ingredient {
+			Ingredient::Item(item) if *amount == 1 => format!("{:?}", item),
+			Ingredient::Item(item) => format!("{} {:?}", amount, item),
+			Ingredient::Category(category) => format!("{} {:?}", amount, category),
+		}).collect::<Vec<_>>().join(" + ")
+	}
+}
+
+#[derive(Resource)]
+pub struct RecipeBook(pub Vec<Recipe>);
+
+impl Default for RecipeBook {
+	fn default() -> Self {
+		use Ingredient::*;
+		Self(vec![
+			Recipe {
+				name: "Garden Soup",
+				ingredients: vec![(2, Category(DropCategory::Veg)), (1, Category(DropCategory::Produce)), (1, Item(DropType::Salt))],
+				relief: 25.0,
+				money: 5.0,
+			},
+			Recipe {
+				name: "Fruit Punch",
+				ingredients: vec![(3, Category(DropCategory::Fruit))],
+				relief: 15.0,
+				money: 3.0,
+			},
+			Recipe {
+				name: "Bread Soup",
+				ingredients: vec![(2, Category(DropCategory::Bakery)), (1, Category(DropCategory::Produce))],
+				relief: 20.0,
+				money: 4.0,
+			},
+			Recipe {
+				name: "Hot Pot",
+				ingredients: vec![(1, Category(DropCategory::Veg)), (2, Category(DropCategory::Produce)), (1, Item(DropType::Pepper))],
+				relief: 25.0,
+				money: 5.0,
+			},
+			Recipe {
+				name: "Fancy Ramen",
+				ingredients: vec![(1, Item(DropType::Ramen)), (1, Category(DropCategory::Produce)), (1, Category(DropCategory::Veg))],
+				relief: 30.0,
+				money: 6.0,
+			},
+		])
+	}
+}
+
+#[derive(Resource, Default)]
+pub struct SoupPot {
+	pub contents: Vec<DropType>,
+	// Index into the RecipeBook of the current card
+	pub recipe: usize,
+}
+
+impl SoupPot {
+	// Tips a mouthful into the soup, returns the recipe if the soup now matches it
+	pub fn serve<'a>(&mut self, mouthful: &[DropType], book: &'a RecipeBook) -> Option<&'a Recipe> {
+		// Older saves could hold more than fits
+		let room = SOUP_SIZE.saturating_sub(self.contents.len());
+		self.contents.extend(mouthful.iter().take(room));
+		let recipe = book.0.get(self.recipe)?;
+		if recipe.matches(&self.contents) {
+			self.contents.clear();
+			self.recipe = (self.recipe + 1 + rand::random::<usize>() % (book.0.len() - 1).max(1)) % book.0.len();
+			Some(recipe)
+		} else {
+			if self.contents.len() >= SOUP_SIZE {
+				self.contents.clear();
+			}
+			None
+		}
+	}
+}
+
+// Ingredients a mouth has eaten on its way down
+#[derive(Component, Default)]
+pub struct Mouthful(pub Vec<DropType>);
+
+// Shows one ingredient in the soup
+#[derive(Component)]
+struct SoupItem(usize);
+
+#[derive(Component)]
+struct RecipeCard;
+
+fn reset_soup(
+	mut pot: ResMut<SoupPot>,
+	book: Res<RecipeBook>,
+) {
+	*pot = SoupPot {
+		contents: Vec::new(),
+		recipe: rand::random::<usize>() % book.0.len().max(1),
+	};
+}
+
+// Goes on the soup target so the ingredients move along with it
+pub fn spawn_soup_items(
+	commands: &mut Commands,
+	soup: Entity,
+) {
+	commands.entity(soup).with_children(|parent| {
+		for i in 0..SOUP_SIZE {
+			parent.spawn(
+				(
+					SpriteBundle {
+						transform: Transform::from_xyz(
+							(i as f32 - (SOUP_SIZE - 1) as f32 / 2.0) * SOUP_ITEM_SPACING,
+							24.0,
+							LABEL_TARGET_DEPTH - TOP_TARGET_DEPTH),
+						visibility: Visibility::Hidden,
+						..default()
+					},
+					SoupItem(i),
+				)
+			);
+		}
+	});
+}
+
+fn spawn_recipe_card(
+	mut commands: Commands,
+	asset_server: Res<AssetServer>,
+) {
+	commands.spawn(
+		(
+			Text2dBundle {
+				text: Text::from_section("", get_vn_text_style(&asset_server))
+					.with_alignment(TextAlignment::Center),
+				transform: Transform::from_xyz(RECIPE_CARD_POSITION.x, RECIPE_CARD_POSITION.y, LABEL_TARGET_DEPTH),
+				..default()
+			},
+			RecipeCard,
+			ClearOnDayTransition,
+		)
+	);
+}
+
+fn update_soup_display(
+	pot: Res<SoupPot>,
+	book: Res<RecipeBook>,
+	asset_server: Res<AssetServer>,
+	mut item_query: Query<(&SoupItem, &mut Handle<Image>, &mut Visibility)>,
+	mut card_query: Query<&mut Text, With<RecipeCard>>,
+) {
+	if !pot.is_changed() {
+		return;
+	}
+	for (item, mut texture, mut visibility) in item_query.iter_mut() {
+		match pot.contents.get(item.0) {
+			Some(drop_type) => {
+				*visibility = Visibility::Visible;
+				*texture = asset_server.load(drop_type.get_path());
+			},
+			None => *visibility = Visibility::Hidden,
+		}
+	}
+	if let Some(recipe) = book.0.get(pot.recipe) {
+		for mut text in card_query.iter_mut() {
+			text.sections[0].value = format!("{}\n{}", recipe.name, recipe.card());
+		}
+	}
+}
+
+fn capture_soup(
+	pot: Res<SoupPot>,
+	mut user_data: ResMut<UserData>,
+) {
+	if let Some(level) = user_data.level.as_mut() {
+		level.soup = pot.contents.clone();
+		level.recipe = pot.recipe;
+	}
+}
+
+fn restore_soup(
+	mut pot: ResMut<SoupPot>,
+	restore: Res<RestoreLevel>,
+	book: Res<RecipeBook>,
+) {
+	pot.contents = restore.0.soup.iter().copied().take(SOUP_SIZE).collect();
+	pot.recipe = restore.0.recipe.min(book.0.len().saturating_sub(1));
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn garden_soup() -> Recipe {
+		RecipeBook::default().0.into_iter().find(|recipe| recipe.name == "Garden Soup").unwrap()
+	}
+
+	#[test]
+	fn soup_with_every_ingredient_matches() {
+		let soup = [DropType::Salt, DropType::Onion, DropType::Cheese, DropType::Pumpkin];
+		assert!(garden_soup().matches(&soup));
+	}
+
+	#[test]
+	fn soup_missing_an_ingredient_does_not_match() {
+		// Two Veg are wanted
+		assert!(!garden_soup().matches(&[DropType::Onion, DropType::Cheese, DropType::Salt]));
+	}
+
+	#[test]
+	fn salt_has_to_go_in() {
+		// Plenty of everything else doesn't stand in for it
+		let soup = [DropType::Onion, DropType::Garlic, DropType::Pumpkin, DropType::Cheese, DropType::Egg];
+		assert!(!garden_soup().matches(&soup));
+		assert!(garden_soup().card().ends_with("+ Salt"));
+	}
+
+	#[test]
+	fn serving_an_overfull_pot_tips_it_out() {
+		let book = RecipeBook::default();
+		let mut pot = SoupPot {contents: vec![DropType::Cd; SOUP_SIZE + 2], recipe: 0};
+		assert!(pot.serve(&[DropType::Apple], &book).is_none());
+		assert!(pot.contents.is_empty());
+	}
+}
@@ -0,0 +1,360 @@
+// Slot machine in the night parlor, like the digital screen on a real pachinko machine
+// Balls falling into the start pocket each bank a spin, spins play out one after another
+
+use bevy::prelude::*;
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+use crate::{
+	game::{physics::BallTargetHit, states::DayState, targets::ClearOnDayTransition, PachinkoSystemSet},
+	setup::UserData,
+};
+
+use super::{MinigameSet, fever::{FeverSpec, Jackpot}};
+
+pub const REEL_COUNT: usize = 3;
+pub const VISIBLE_ROWS: usize = 3;
+// Spins banked beyond this are lost
+pub const MAX_BANKED_SPINS: u8 = 4;
+
+// Seconds until the first reel stops, the others follow one after another
+const FIRST_STOP_TIME: f32 = 1.0;
+const REEL_STOP_GAP: f32 = 0.5;
+const SYMBOLS_PER_SECOND: f32 = 15.0;
+// How long the stopped reels stay up before the next spin
+const RESULT_TIME: f32 = 1.5;
+
+const MACHINE_POSITION: Vec2 = Vec2::new(1300.0, 560.0);
+const SYMBOL_SPACING: f32 = 56.0;
+const SYMBOL_SCALE: f32 = 1.5;
+const SLOTS_DEPTH: f32 = -0.2;
+
+// Lined up along the middle row only by winning the lottery
+const JACKPOT_SYMBOL: Symbol = Symbol::Seven;
+
+// Rows of each reel that make up a payline, top row is 0
+const PAYLINES: [[usize; REEL_COUNT]; 5] = [
+	[1, 1, 1],
+	[0, 0, 0],
+	[2, 2, 2],
+	[0, 1, 2],
+	[2, 1, 0],
+];
+
+// How often each symbol appears on a reel strip, rarer symbols pay more
+const STRIP_WEIGHTS: [(Symbol, usize); 5] = [
+	(Symbol::Cherry, 7),
+	(Symbol::Lemon, 6),
+	(Symbol::Melon, 5),
+	(Symbol::Diamond, 3),
+	(Symbol::Seven, 1),
+];
+
+pub struct SlotsPlugin;
+
+impl Plugin for SlotsPlugin {
+	fn build(&self, app: &mut App) {
+		app
+		.init_resource::<SlotMachine>()
+		.init_resource::<SlotRng>()
+		.init_resource::<SlotSpins>()
+		.add_system(reset_slots.in_schedule(OnEnter(DayState::Night)))
+		.add_system(bank_spins
+			.run_if(on_event::<BallTargetHit>())
+			.in_set(MinigameSet(DayState::Night))
+			.in_set(PachinkoSystemSet))
+		.add_system(start_spin
+			.in_set(MinigameSet(DayState::Night))
+			.after(bank_spins))
+		.add_system(turn_reels
+			.in_set(MinigameSet(DayState::Night))
+			.after(start_spin))
+		;
+	}
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Symbol {
+	Cherry,
+	Lemon,
+	Melon,
+	Diamond,
+	Seven,
+}
+
+impl Symbol {
+	pub fn get_path(&self) -> &'static str {
+		match self {
+			Symbol::Cherry => "slots/cherry.png",
+			Symbol::Lemon => "slots/lemon.png",
+			Symbol::Melon => "slots/melon.png",
+			Symbol::Diamond => "slots/diamond.png",
+			Symbol::Seven => "slots/seven.png",
+		}
+	}
+
+	// Money paid for a line of three
+	pub fn payout(&self) -> f32 {
+		match self {
+			Symbol::Cherry => 2.0,
+			Symbol::Lemon => 3.0,
+			Symbol::Melon => 5.0,
+			Symbol::Diamond => 10.0,
+			Symbol::Seven => 30.0,
+		}
+	}
+}
+
+// Middle hole of the parlor, balls landing here bank a spin
+#[derive(Component)]
+pub struct StartPocket;
+
+// One of the symbols in view on the machine
+#[derive(Component)]
+struct SlotSymbol {
+	reel: usize,
+	row: usize,
+}
+
+#[derive(Resource)]
+pub struct SlotMachine {
+	pub reels: [Vec<Symbol>; REEL_COUNT],
+}
+
+impl Default for SlotMachine {
+	fn default() -> Self {
+		let strip = weighted_strip(&STRIP_WEIGHTS);
+		Self {
+			reels: [strip.clone(), strip.clone(), strip],
+		}
+	}
+}
+
+impl SlotMachine {
+	// Where each reel stops, as an index into its strip for the middle row
+	pub fn spin<R: Rng>(&self, rng: &mut R) -> [usize; REEL_COUNT] {
+		let mut stops = [0; REEL_COUNT];
+		for (stop, reel) in stops.iter_mut().zip(self.reels.iter()) {
+			*stop = rng.gen_range(0..reel.len());
+		}
+		stops
+	}
+
+	// Symbol in view on a reel, position is where the middle row of the reel is on its strip
+	pub fn symbol(&self, reel: usize, position: usize, row: usize) -> Symbol {
+		let strip = &self.reels[reel];
+		strip[(position + strip.len() + row - 1) % strip.len()]
+	}
+
+	// Paylines that came up with the same symbol on every reel
+	pub fn winning_lines(&self, stops: &[usize; REEL_COUNT]) -> Vec<(usize, Symbol)> {
+		PAYLINES.iter().enumerate().filter_map(|(line, rows)| {
+			let first = self.symbol(0, stops[0], rows[0]);
+			(1..REEL_COUNT).all(|reel| self.symbol(reel, stops[reel], rows[reel]) == first)
+				.then_some((line, first))
+		}).collect()
+	}
+
+	// Stops showing the jackpot symbol along the middle row, None if a reel doesn't have it
+	pub fn jackpot_stops(&self) -> Option<[usize; REEL_COUNT]> {
+		let mut stops = [0; REEL_COUNT];
+		for (stop, reel) in stops.iter_mut().zip(self.reels.iter()) {
+			*stop = reel.iter().position(|symbol| *symbol == JACKPOT_SYMBOL)?;
+		}
+		Some(stops)
+	}
+
+	pub fn is_jackpot(&self, stops: &[usize; REEL_COUNT]) -> bool {
+		(0..REEL_COUNT).all(|reel| self.symbol(reel, stops[reel], 1) == JACKPOT_SYMBOL)
+	}
+
+	pub fn payout(&self, stops: &[usize; REEL_COUNT]) -> f32 {
+		self.winning_lines(stops).iter().map(|(_, symbol)| symbol.payout()).sum()
+	}
+}
+
+// Symbols spread out along the strip so the common ones don't bunch up
+fn weighted_strip(weights: &[(Symbol, usize)]) -> Vec<Symbol> {
+	let rounds = weights.iter().map(|(_, weight)| *weight).max().unwrap_or(0);
+	(0..rounds)
+		.flat_map(|round| weights.iter().filter(move |(_, weight)| *weight > round).map(|(symbol, _)| *symbol))
+		.collect()
+}
+
+// Replace with a seeded StdRng to get the same spins every time
+#[derive(Resource)]
+pub struct SlotRng(pub StdRng);
+
+impl Default for SlotRng {
+	fn default() -> Self {
+		Self(StdRng::from_entropy())
+	}
+}
+
+#[derive(Resource, Default)]
+pub struct SlotSpins {
+	pub banked: u8,
+	current: Option<Spin>,
+}
+
+struct Spin {
+	stops: [usize; REEL_COUNT],
+	elapsed: f32,
+	paid: bool,
+	jackpot: bool,
+}
+
+fn reset_slots(
+	mut spins: ResMut<SlotSpins>,
+) {
+	*spins = SlotSpins::default();
+}
+
+fn bank_spins(
+	mut collision_events: EventReader<BallTargetHit>,
+	pocket_query: Query<(), With<StartPocket>>,
+	mut spins: ResMut<SlotSpins>,
+) {
+	for ball_event in collision_events.iter() {
+		if pocket_query.get(ball_event.target).is_ok() {
+			spins.banked = (spins.banked + 1).min(MAX_BANKED_SPINS);
+		}
+	}
+}
+
+fn start_spin(
+	mut commands: Commands,
+	asset_server: Res<AssetServer>,
+	machine: Res<SlotMachine>,
+	mut rng: ResMut<SlotRng>,
+	mut spins: ResMut<SlotSpins>,
+	spec: Res<FeverSpec>,
+	symbol_query: Query<(), With<SlotSymbol>>,
+) {
+	if spins.current.is_some() || spins.banked == 0 {
+		return;
+	}
+	spins.banked -= 1;
+	// The lottery is drawn first, the reels only show its result
+	let won = rng.0.gen_bool(spec.jackpot_odds);
+	let stops = match machine.jackpot_stops() {
+		Some(stops) if won => stops,
+		_ => {
+			let mut stops = machine.spin(&mut rng.0);
+			while machine.is_jackpot(&stops) {
+				stops = machine.spin(&mut rng.0);
+			}
+			stops
+		},
+	};
+	let jackpot = machine.is_jackpot(&stops);
+	spins.current = Some(Spin { stops, elapsed: 0.0, paid: false, jackpot });
+	if !symbol_query.is_empty() {
+		return;
+	}
+	for (reel, stop) in stops.iter().enumerate() {
+		for row in 0..VISIBLE_ROWS {
+			commands.spawn(
+				(
+					SpriteBundle {
+						transform: Transform::from_xyz(
+							MACHINE_POSITION.x + (reel as f32 - 1.0) * SYMBOL_SPACING,
+							MACHINE_POSITION.y - (row as f32 - 1.0) * SYMBOL_SPACING,
+							SLOTS_DEPTH)
+							.with_scale(Vec3::splat(SYMBOL_SCALE)),
+						texture: asset_server.load(machine.symbol(reel, *stop, row).get_path()),
+						..default()
+					},
+					SlotSymbol { reel, row },
+					ClearOnDayTransition,
+				)
+			);
+		}
+	}
+}
+
+#[allow(clippy::too_many_arguments)]
+fn turn_reels(
+	mut commands: Commands,
+	asset_server: Res<AssetServer>,
+	machine: Res<SlotMachine>,
+	mut spins: ResMut<SlotSpins>,
+	mut symbol_query: Query<(Entity, &SlotSymbol, &mut Handle<Image>, &mut Sprite)>,
+	mut user_data: ResMut<UserData>,
+	mut jackpots: EventWriter<Jackpot>,
+	time: Res<Time>,
+) {
+	let Some(spin) = spins.current.as_mut() else {
+		return;
+	};
+	spin.elapsed += time.delta_seconds();
+	let last_stop = FIRST_STOP_TIME + (REEL_COUNT - 1) as f32 * REEL_STOP_GAP;
+	let winning_lines = if spin.elapsed >= last_stop {machine.winning_lines(&spin.stops)} else {Vec::new()};
+	for (_, symbol, mut texture, mut sprite) in symbol_query.iter_mut() {
+		let stop_time = FIRST_STOP_TIME + symbol.reel as f32 * REEL_STOP_GAP;
+		// Still turning reels count down towards where they'll stop
+		let turns_left = ((stop_time - spin.elapsed).max(0.0) * SYMBOLS_PER_SECOND) as usize;
+		let position = spin.stops[symbol.reel] + turns_left;
+		*texture = asset_server.load(machine.symbol(symbol.reel, position, symbol.row).get_path());
+		let winner = winning_lines.iter().any(|(line, _)| PAYLINES[*line][symbol.reel] == symbol.row);
+		sprite.color = if winner {Color::GOLD} else {Color::WHITE};
+	}
+	if spin.elapsed >= last_stop && !spin.paid {
+		spin.paid = true;
+		user_data.money = (user_data.money + machine.payout(&spin.stops)).clamp(0.0, 100.0);
+		if spin.jackpot {
+			jackpots.send(Jackpot);
+		}
+	}
+	if spin.elapsed >= last_stop + RESULT_TIME {
+		spins.current = None;
+		// Keep the machine up if there's another spin waiting
+		if spins.banked == 0 {
+			for (entity, ..) in symbol_query.iter() {
+				commands.entity(entity).despawn_recursive();
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn same_seed_same_spins() {
+		let machine = SlotMachine::default();
+		let mut first = SlotRng(StdRng::seed_from_u64(7));
+		let mut second = SlotRng(StdRng::seed_from_u64(7));
+		for _ in 0..100 {
+			let stops = machine.spin(&mut first.0);
+			assert_eq!(stops, machine.spin(&mut second.0));
+		}
+	}
+
+	#[test]
+	fn strips_follow_weights() {
+		let machine = SlotMachine::default();
+		for reel in machine.reels.iter() {
+			for (symbol, weight) in STRIP_WEIGHTS {
+				assert_eq!(reel.iter().filter(|s| **s == symbol).count(), weight);
+			}
+		}
+	}
+
+	#[test]
+	fn three_sevens_pay_on_every_line() {
+		let machine = SlotMachine {
+			reels: [vec![Symbol::Seven; 3], vec![Symbol::Seven; 3], vec![Symbol::Seven; 3]],
+		};
+		assert_eq!(machine.winning_lines(&[0, 1, 2]).len(), PAYLINES.len());
+		assert_eq!(machine.payout(&[0, 1, 2]), Symbol::Seven.payout() * PAYLINES.len() as f32);
+	}
+
+	#[test]
+	fn jackpot_stops_line_up_the_middle_row() {
+		let machine = SlotMachine::default();
+		let stops = machine.jackpot_stops().unwrap();
+		assert!(machine.is_jackpot(&stops));
+		assert!(machine.winning_lines(&stops).contains(&(0, JACKPOT_SYMBOL)));
+	}
+}
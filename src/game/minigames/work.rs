@@ -1,46 +1,90 @@
+use std::time::Duration;
+
 use bevy::{prelude::*, math::Vec3Swizzles, window::PrimaryWindow};
+use rand::seq::SliceRandom;
 
 use crate::{
 	setup::UserData,
+	audio::sfx::{PlaySfx, Sfx},
+	save::autosave::{AutosaveSet, RestoreLevel, HangingBall},
 	game::{
 		physics::{BallTargetHit, Velocity}, 
-		food::DropType, PachinkoSystemSet, states::{DayState, GameState, PauseState},
-		targets::{LinkedBaskets, Target, BOTTOM_TARGET_DEPTH, LABEL_TARGET_DEPTH, TOP_TARGET_DEPTH, ClearOnDayTransition},
+		food::DropType, PachinkoSystemSet, states::DayState,
+		targets::{BasketLabel, Target, LABEL_TARGET_DEPTH, ClearOnDayTransition,
+			TargetDetails, TargetPair},
 		balls::{Ball, DropZone, BALL_DEPTH, Held, HangTimer}, pegs::{Peg, PEG_DEPTH}
 	}
 };
 
-use super::parlor::{MoveHorizontalPeg, MoveVerticalPeg, HORIZONTAL_SPEED, VERTICAL_SPEED};
+use super::{MoveHorizontalPeg, MoveVerticalPeg, Minigame, MinigameDetails, MinigameSet, shallow_triangle,
+	orders::{OrdersPlugin, OrderQueue, WRONG_BASKET_STRESS}};
 
 pub const HANG_TIME: f32 = 10.0;
 pub const SPAWN_TIME: f32 = 1.0;
+// Chance of a new ball being something a customer is waiting for
+pub const WANTED_CHANCE: f32 = 0.3;
+// Items the storage bin holds before overflowing, and what losing one to overflow costs
+pub const STORAGE_CAPACITY: usize = 4;
+pub const OVERFLOW_STRESS: f32 = 5.0;
+const STORAGE_SIZE: Vec2 = Vec2::new(100.0, 50.0);
 
 pub struct WorkMinigame;
 
-#[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone, Copy)]
-pub struct WorkSystemSet;
+const WORK_SET: MinigameSet = MinigameSet(DayState::Morning);
+
+impl Minigame for WorkMinigame {
+	fn details(&self) -> MinigameDetails {
+		MinigameDetails {
+			state: DayState::Morning,
+			title: "Work - Morning",
+			tutorial: "backgrounds/work.png",
+			background: |_| "backgrounds/mart.png".to_string(),
+			music: "audio/bgm/jpeg_of_a_sunset.ogg",
+			high_music: "audio/bgm/deep_fried_sunset.ogg",
+			targets: TargetDetails {
+				amount: 5,
+				width: 128.0,
+				height: 32.0,
+				spacing: 60.0,
+				speed: 0.0,
+				top_sprite: "sprites/handle.png",
+				bottom_sprite: "sprites/basket.png",
+				decorate: label_baskets,
+			},
+			// Balls come from the storage bin instead
+			drop_type: || None,
+			peg_speed: Vec2::new(5.0, 6.0),
+		}
+	}
+}
 
 impl Plugin for WorkMinigame {
 	fn build(&self, app: &mut App) {
 		app
-		.configure_set(WorkSystemSet
-			.run_if(in_state(DayState::Morning))
-			.run_if(in_state(PauseState::Unpaused))
-			.run_if(in_state(GameState::Game)))
+		.add_plugin(OrdersPlugin)
+		.init_resource::<StorageContents>()
 		.add_system(handle_work_balls
 			.run_if(on_event::<BallTargetHit>())
 			.run_if(in_state(DayState::Morning))
 			.in_set(PachinkoSystemSet))
 		.add_system(move_targets
-			.in_set(WorkSystemSet)
+			.in_set(WORK_SET)
 			.before(PachinkoSystemSet))
-		.add_systems((spawn_work_ball, drop_work_ball, grab_work_ball).in_set(WorkSystemSet))
+		.add_systems((spawn_work_ball, drop_work_ball, grab_work_ball).in_set(WORK_SET))
 		.add_system(initialise_spawn_timer.in_schedule(OnEnter(DayState::Morning)))
 		.add_system(spawn_work_pegs.in_schedule(OnEnter(DayState::Morning)))
 		.add_system(spawn_storage.in_schedule(OnEnter(DayState::Morning)))
-		.add_system(store_balls.in_set(WorkSystemSet))
-		.add_system(move_pegs_horizontal.in_set(WorkSystemSet))
-		.add_system(move_pegs_vertical.in_set(WorkSystemSet))
+		.add_system(store_balls.in_set(WORK_SET))
+		.add_system(update_storage_display
+			.in_set(WORK_SET)
+			.after(store_balls)
+			.after(grab_work_ball))
+		.add_system(capture_work_board
+			.in_set(AutosaveSet::Capture)
+			.run_if(in_state(DayState::Morning)))
+		.add_system(restore_work_board
+			.in_set(AutosaveSet::Restore)
+			.run_if(in_state(DayState::Morning)))
 		;
 	}
 }
@@ -51,6 +95,14 @@ pub struct SpawnTimer(pub Timer);
 #[derive(Component)]
 pub struct Storage;
 
+// Items parked in the storage bin, the last one in is the first one out
+#[derive(Resource, Default)]
+pub struct StorageContents(pub Vec<DropType>);
+
+// Shows what's in one space of the storage bin
+#[derive(Component)]
+struct StoredItem(usize);
+
 #[derive(Component)]
 pub struct Basket;
 
@@ -63,11 +115,29 @@ pub fn initialise_spawn_timer(
 	commands.insert_resource(SpawnTimer(Timer::from_seconds(SPAWN_TIME, TimerMode::Repeating)));
 }
 
-fn shallow_triangle(
-	top_x: f32,
-	top_y: f32,
-) -> Vec<(f32, f32)> {
-	vec![(top_x, top_y),(top_x-25.0,top_y-20.0),(top_x-50.0,top_y-40.0),(top_x+25.0,top_y-20.0),(top_x+50.0,top_y-40.0)]
+// Every basket gets a label for the item it wants
+fn label_baskets(
+	commands: &mut Commands,
+	asset_server: &Res<AssetServer>,
+	pair: &TargetPair,
+) {
+	commands.entity(pair.top).insert(Handle);
+	commands.entity(pair.bottom).insert(Basket);
+	let drop_type: DropType = rand::random();
+	commands.spawn(
+		(
+			SpriteBundle {
+				transform: Transform::from_xyz(pair.x, 16.0, LABEL_TARGET_DEPTH),
+				texture: asset_server.load(drop_type.get_path()),
+				..default()
+			},
+			ClearOnDayTransition,
+			Velocity(Vec2::new(pair.speed, 0.0)),
+			drop_type,
+			Target {radius: 0.5 * pair.width},
+			BasketLabel,
+		)
+	);
 }
 
 fn spawn_work_pegs(
@@ -182,69 +252,25 @@ fn spawn_work_pegs(
 }
 
 fn handle_work_balls(
-	mut commands: Commands,
 	mut collision_events: EventReader<BallTargetHit>,
 	mut user_data: ResMut<UserData>,
-	basket_query: Query<(Entity, &LinkedBaskets, &Transform, &DropType, &Velocity, &Target)>,
-	asset_server: Res<AssetServer>,
+	mut queue: ResMut<OrderQueue>,
+	label_query: Query<&DropType, With<BasketLabel>>,
+	mut sfx: EventWriter<PlaySfx>,
 ) {
 	for ball_event in collision_events.iter() {
-		let target_entity = ball_event.target;
-		let ball_type = ball_event.ball_type;
-		if let Ok((basket_entity, &linked_baskets, &basket_transform, &basket_type, &basket_velocity, &basket_target)) = basket_query.get(target_entity) {
-			if basket_type == ball_type {
-				user_data.money = (user_data.money + 10.0).clamp(0.0, 100.0);
-				let top_entity = commands.spawn(
-					(
-						SpriteBundle {
-							transform: Transform::from_xyz(basket_transform.translation.x, 32.0, TOP_TARGET_DEPTH),
-							texture: asset_server.load("sprites/handle.png"),
-							..default()
-						},
-						basket_velocity,
-						//basket_target,
-						Handle,
-						ClearOnDayTransition
-					)
-				).id();
-				let bottom_entity = commands.spawn(
-					(
-						SpriteBundle {
-							transform: Transform::from_xyz(basket_transform.translation.x, 32.0, BOTTOM_TARGET_DEPTH),
-							texture: asset_server.load("sprites/basket.png"),
-							..default()
-						},
-						basket_velocity,
-						//basket_target,
-						Basket,
-						ClearOnDayTransition
-					)
-				).id();
-				let drop_type: DropType = rand::random();
-				commands.spawn(
-					(
-						SpriteBundle {
-							transform: Transform::from_xyz(basket_transform.translation.x, 16.0, LABEL_TARGET_DEPTH),
-							texture: asset_server.load(drop_type.get_path()),
-							..default()
-						},
-						basket_velocity,
-						drop_type,
-						basket_target,
-						LinkedBaskets((top_entity, bottom_entity)),
-						ClearOnDayTransition
-					)
-				);
-				commands.entity(linked_baskets.0.0).despawn_recursive();
-				commands.entity(linked_baskets.0.1).despawn_recursive();
-				commands.entity(basket_entity).despawn_recursive();
+		if let Ok(&basket_type) = label_query.get(ball_event.target) {
+			if basket_type == ball_event.ball_type {
+				let pay = queue.fill(basket_type);
+				user_data.money = (user_data.money + pay).clamp(0.0, 100.0);
+				sfx.send(Sfx::BasketHit.into());
 			} else {
-				//println!("Incorrect Basket");
-				user_data.money = (user_data.money + 1.0).clamp(0.0, 100.0);
+				queue.miss();
+				user_data.stress = (user_data.stress + WRONG_BASKET_STRESS).clamp(0.0, 100.0);
+				sfx.send(Sfx::BasketMiss.into());
 			}
-			user_data.stress = (user_data.stress + 5.0).clamp(0.0, 100.0)
 		} else {
-			// TODO: handle, this is probably balls falling in storage
+			// Only basket labels are targets in the morning, the storage bin catches balls in store_balls
 			warn!("Balls are falling into targets which are inapropriate for current daytime. (Ball fell into non-basket)");
 		}
 	}
@@ -265,7 +291,9 @@ fn move_targets(
 fn spawn_storage(
 	mut commands: Commands,
 	asset_server: Res<AssetServer>,
+	mut contents: ResMut<StorageContents>,
 ) {
+	contents.0.clear();
 	commands.spawn(
 		(
 			SpriteBundle {
@@ -277,25 +305,72 @@ fn spawn_storage(
 			Velocity(Vec2::new(100.0, 0.0)),
 			ClearOnDayTransition,
 		)
-	);
+	).with_children(|parent| {
+		for i in 0..STORAGE_CAPACITY {
+			parent.spawn(
+				(
+					SpriteBundle {
+						transform: Transform::from_xyz((i as f32 - (STORAGE_CAPACITY - 1) as f32 / 2.0) * 22.0, 0.0, 0.01)
+							.with_scale(Vec3::splat(0.6)),
+						visibility: Visibility::Hidden,
+						..default()
+					},
+					StoredItem(i),
+				)
+			);
+		}
+	});
 }
 
+// Balls loose on the board, neither held nor hanging
+type LooseBalls<'w, 's> = Query<'w, 's, (Entity, &'static Transform, &'static DropType), (With<Ball>, Without<Held>, Without<HangTimer>)>;
+
 fn store_balls(
 	mut commands: Commands,
-	mut ball_query: Query<(Entity, &Transform), With<Ball>>,
+	mut ball_query: LooseBalls,
 	storage_query: Query<&Transform, With<Storage>>,
+	mut contents: ResMut<StorageContents>,
+	mut user_data: ResMut<UserData>,
 ) {
 	let storage_transform = storage_query.single();
-	for (entity, ball_transform) in ball_query.iter_mut() {
+	for (entity, ball_transform, &drop_type) in ball_query.iter_mut() {
 		if ball_transform.translation.x > storage_transform.translation.x - 50.0 
 		&& ball_transform.translation.x < storage_transform.translation.x + 50.0 
 		&& ball_transform.translation.y > storage_transform.translation.y - 25.0 
 		&& ball_transform.translation.y < storage_transform.translation.y {
 			commands.entity(entity).despawn_recursive();
+			if contents.0.len() < STORAGE_CAPACITY {
+				contents.0.push(drop_type);
+			} else {
+				// No room, the item is lost
+				user_data.stress = (user_data.stress + OVERFLOW_STRESS).clamp(0.0, 100.0);
+			}
 		}
 	}
 }
 
+fn update_storage_display(
+	contents: Res<StorageContents>,
+	asset_server: Res<AssetServer>,
+	mut storage_query: Query<&mut Sprite, With<Storage>>,
+	mut item_query: Query<(&StoredItem, &mut bevy::asset::Handle<Image>, &mut Visibility)>,
+) {
+	for mut sprite in storage_query.iter_mut() {
+		// Turns red once the next item would overflow
+		sprite.color = if contents.0.len() >= STORAGE_CAPACITY {Color::rgb(1.0, 0.4, 0.4)} else {Color::WHITE};
+	}
+	for (item, mut texture, mut visibility) in item_query.iter_mut() {
+		match contents.0.get(item.0) {
+			Some(drop_type) => {
+				*visibility = Visibility::Visible;
+				*texture = asset_server.load(drop_type.get_path());
+			},
+			None => *visibility = Visibility::Hidden,
+		}
+	}
+}
+
+#[allow(clippy::too_many_arguments)]
 fn spawn_work_ball(
 	mut commands: Commands,
 	asset_server: Res<AssetServer>,
@@ -304,6 +379,7 @@ fn spawn_work_ball(
 	mut spawn_timer: ResMut<SpawnTimer>,
 	time: Res<Time>,
 	user_data: Res<UserData>,
+	queue: Res<OrderQueue>,
 ) {
 	spawn_timer.0.tick(time.delta() / (user_data.royal/10.0).clamp(1.0, 10.0) as u32);
 
@@ -321,7 +397,11 @@ fn spawn_work_ball(
 			}
 		}
 		if !collision {
-			let drop_type: DropType = rand::random();
+			let wanted = queue.wanted();
+			let drop_type: DropType = match wanted.choose(&mut rand::thread_rng()) {
+				Some(&drop_type) if rand::random::<f32>() < WANTED_CHANCE => drop_type,
+				_ => rand::random(),
+			};
 			commands.spawn(
 				(
 					SpriteBundle {
@@ -357,7 +437,10 @@ fn grab_work_ball (
 	mut commands: Commands,
 	window_query: Query<&Window, With<PrimaryWindow>>,
 	mouse: Res<Input<MouseButton>>,
-	ball_query: Query<(Entity, &Transform, With<HangTimer>)>
+	ball_query: Query<(Entity, &Transform, With<HangTimer>)>,
+	storage_query: Query<&Transform, With<Storage>>,
+	mut contents: ResMut<StorageContents>,
+	asset_server: Res<AssetServer>,
 ) {
 	let window = window_query.get_single().unwrap();
 	for (entity, transform, _) in ball_query.iter() {
@@ -370,28 +453,83 @@ fn grab_work_ball (
 			}
 		}
 	}
+	// Clicking the storage bin pulls the last stored item back out, ready to be flung
+	if mouse.just_pressed(MouseButton::Left) {
+		if let (Some(current_pos), Ok(storage_transform)) = (window.cursor_position(), storage_query.get_single()) {
+			let offset = current_pos - storage_transform.translation.xy();
+			if offset.x.abs() < STORAGE_SIZE.x / 2.0 && offset.y.abs() < STORAGE_SIZE.y / 2.0 {
+				if let Some(drop_type) = contents.0.pop() {
+					commands.spawn(
+						(
+							SpriteBundle {
+								transform: Transform::from_xyz(
+									storage_transform.translation.x,
+									storage_transform.translation.y + STORAGE_SIZE.y / 2.0 + 16.0,
+									BALL_DEPTH),
+								texture: asset_server.load(drop_type.get_path()),
+								..default()
+							},
+							Ball,
+							Held,
+							drop_type,
+						)
+					);
+				}
+			}
+		}
+	}
 }
 
-fn move_pegs_horizontal(
-	mut horizontal_peg_query: Query<(&mut Transform, &mut MoveHorizontalPeg)>,
-	time: Res<Time>,
+fn capture_work_board(
+	label_query: Query<(&Transform, &DropType), With<BasketLabel>>,
+	hanging_ball_query: Query<(&Transform, &DropType, &HangTimer), With<Ball>>,
+	contents: Res<StorageContents>,
+	mut user_data: ResMut<UserData>,
 ) {
-	for (mut transform, mut move_peg) in horizontal_peg_query.iter_mut() {
-		if move_peg.1 {transform.translation.x += HORIZONTAL_SPEED * 5.0 * time.delta_seconds()
-			} else {transform.translation.x += -HORIZONTAL_SPEED * 5.0 * time.delta_seconds()};
-		if transform.translation.x >= move_peg.0 + 125.0 {move_peg.1 = false};
-		if transform.translation.x <= move_peg.0 - 125.0 {move_peg.1 = true};
+	if let Some(level) = user_data.level.as_mut() {
+		level.stored = contents.0.clone();
+		let mut labels: Vec<(&Transform, &DropType)> = label_query.iter().collect();
+		labels.sort_by(|a, b| a.0.translation.x.total_cmp(&b.0.translation.x));
+		level.basket_labels = labels.into_iter().map(|(_, &drop_type)| drop_type).collect();
+		for (transform, &drop_type, hang_timer) in hanging_ball_query.iter() {
+			level.hanging_balls.push(HangingBall {
+				x: transform.translation.x,
+				y: transform.translation.y,
+				drop_type,
+				elapsed: hang_timer.0.elapsed_secs(),
+			});
+		}
 	}
 }
 
-fn move_pegs_vertical(
-	mut vertical_peg_query: Query<(&mut Transform, &mut MoveVerticalPeg)>,
-	time: Res<Time>,
+fn restore_work_board(
+	mut commands: Commands,
+	mut label_query: Query<(&Transform, &mut DropType, &mut bevy::asset::Handle<Image>), With<BasketLabel>>,
+	restore: Res<RestoreLevel>,
+	asset_server: Res<AssetServer>,
+	mut contents: ResMut<StorageContents>,
 ) {
-	for (mut transform, mut move_peg) in vertical_peg_query.iter_mut() {
-		if move_peg.1 {transform.translation.y += VERTICAL_SPEED * 6.0 * time.delta_seconds()
-			} else {transform.translation.y += -VERTICAL_SPEED * 6.0 * time.delta_seconds()};
-		if transform.translation.y >= move_peg.0 + 120.0 {move_peg.1 = false};
-		if transform.translation.y <= move_peg.0 {move_peg.1 = true};
+	contents.0 = restore.0.stored.clone();
+	let mut labels: Vec<_> = label_query.iter_mut().collect();
+	labels.sort_by(|a, b| a.0.translation.x.total_cmp(&b.0.translation.x));
+	for ((_, drop_type, texture), &saved_type) in labels.iter_mut().zip(restore.0.basket_labels.iter()) {
+		**drop_type = saved_type;
+		**texture = asset_server.load(saved_type.get_path());
 	}
-}
\ No newline at end of file
+	for ball in restore.0.hanging_balls.iter() {
+		let mut hang_timer = Timer::from_seconds(HANG_TIME, TimerMode::Once);
+		hang_timer.set_elapsed(Duration::from_secs_f32(ball.elapsed));
+		commands.spawn(
+			(
+				SpriteBundle {
+					transform: Transform::from_xyz(ball.x, ball.y, BALL_DEPTH),
+					texture: asset_server.load(ball.drop_type.get_path()),
+					..default()
+				},
+				Ball,
+				HangTimer(hang_timer),
+				ball.drop_type,
+			)
+		);
+	}
+}
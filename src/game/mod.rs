@@ -6,11 +6,13 @@ mod balls;
 pub mod pegs;
 pub mod states;
 pub mod food;
+pub mod loot;
 pub mod physics;
 #[cfg(debug_assertions)]
 mod debug;
 pub mod targets;
-mod minigames;
+pub mod minigames;
+pub mod calendar;
 
 // Plugins
 use self::backgrounds::BackgroundsPlugin;
@@ -19,8 +21,7 @@ use self::states::StatesPlugin;
 use self::physics::PhysicsPlugin;
 #[cfg(debug_assertions)]
 use self::debug::GameDebugPlugin;
-use self::targets::TargetsPlugin;
-use self::minigames::{FoodMinigame, WorkMinigame, ParlorMinigame};
+use self::minigames::{MinigamesPlugin, AddMinigame, FoodMinigame, WorkMinigame, ParlorMinigame};
 
 pub struct GamePlugin;
 
@@ -37,11 +38,11 @@ impl Plugin for GamePlugin {
 		.add_plugin(BallsPlugin)
 		.add_plugin(PhysicsPlugin)
 		.add_plugin(StatesPlugin)
-		.add_plugin(TargetsPlugin)
+		.add_plugin(MinigamesPlugin)
 		// Individual minigames
-		.add_plugin(FoodMinigame)
-		.add_plugin(WorkMinigame)
-		.add_plugin(ParlorMinigame)
+		.add_minigame(FoodMinigame)
+		.add_minigame(WorkMinigame)
+		.add_minigame(ParlorMinigame)
 		;
 		#[cfg(debug_assertions)]
 		app.add_plugin(GameDebugPlugin);
@@ -7,6 +7,10 @@ pub const PEG_DEPTH: f32 = -1.0;
 #[derive(Component, Default, Clone, Copy)]
 pub struct Peg(pub PegType);
 
+// Spawn order of a peg within its level, used to find it again when restoring an autosave
+#[derive(Component, Clone, Copy)]
+pub struct BoardIndex(pub usize);
+
 #[derive(Default, Clone, Copy, PartialEq, Eq)]
 pub enum PegType {
 	#[default]
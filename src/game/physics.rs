@@ -3,7 +3,7 @@
 
 use bevy::{prelude::*, math::Vec3Swizzles};
 
-use super::{balls::{Ball, HangTimer}, pegs::{Peg, PegType}, states::Frame, PachinkoSystemSet, targets::Target, food::DropType};
+use super::{balls::{Ball, HangTimer}, pegs::{Peg, PegType}, states::Frame, PachinkoSystemSet, targets::Target, food::{DropType, DropPhysics}};
 
 pub struct PhysicsPlugin;
 
@@ -28,6 +28,8 @@ impl Plugin for PhysicsPlugin {
 #[derive(Clone, Copy, PartialEq)]
 pub struct BallCollisionEvent {
 	pub pos: Vec2,
+	// How fast the ball was heading into whatever it hit
+	pub speed: f32,
 	pub collision_type: BallCollisionEventType,
 }
 
@@ -45,6 +47,7 @@ pub enum BallCollisionEventType {
 #[derive(Clone, Copy, PartialEq)]
 pub struct BallTargetHit {
 	pub ball_type: DropType,
+	pub ball: Entity,
 	pub target: Entity,
 }
 
@@ -65,14 +68,18 @@ pub fn move_ball(
 	}
 }
 
+// Balls in flight, droppables among them fall at their own rate
+type FallingBalls<'w, 's> = Query<'w, 's, (&'static mut Velocity, Option<&'static DropType>), (With<Ball>, Without<HangTimer>)>;
+
 fn apply_gravity(
-	mut velocity_query: Query<&mut Velocity, (With<Ball>, Without<HangTimer>)>,
+	mut velocity_query: FallingBalls,
 	time: Res<Time>,
 	gravity: Res<Gravity>,
 	//user_data: Res<UserData>,
 ) {
-	for mut velocity in velocity_query.iter_mut() {
-		velocity.0.y += gravity.0 * time.delta_seconds();
+	for (mut velocity, drop_type) in velocity_query.iter_mut() {
+		let scale = drop_type.map(|drop_type| drop_type.info().physics.gravity).unwrap_or(1.0);
+		velocity.0.y += gravity.0 * scale * time.delta_seconds();
 		//velocity.0.y += gravity.0 * time.delta_seconds() * (1.0 - (user_data.royal as f32 / 100.0)).clamp(0.2, 1.0);
 	}
 }
@@ -139,17 +146,19 @@ fn ball_peg_collide(
 	time: Res<Time>,
 	mut collision_events: EventWriter<BallCollisionEvent>,
 ) {
-	let bounciness = 80.0; //50.0 is normalish, 100.0 very bouncy
 	for (ball_entity, mut ball_transform, mut velocity, drop_type) in ball_query.iter_mut() {
+		let bounciness = drop_type.map(|drop_type| drop_type.info().physics.bounciness).unwrap_or(DropPhysics::default().bounciness);
 		for (peg_entity, &peg_transform, &peg) in peg_query.iter() {
 			let offset = (ball_transform.translation - peg_transform.translation).xy();
 			if offset.length_squared() <= 32.0 * 32.0 {
+				let speed = Vec2::dot(velocity.0, offset.normalize()).abs();
 				let dp = offset * Vec2::dot(velocity.0, offset) / ((offset.length_squared()));
 				velocity.0 -= dp * bounciness * time.delta_seconds();
 				let push = offset.normalize() * 1.01 * 32.0 - offset;
 				ball_transform.translation += push.extend(0.0);
 				collision_events.send(BallCollisionEvent {
 					pos: ball_transform.translation.xy(),
+					speed,
 					collision_type: BallCollisionEventType::Peg {
 						ball_type: drop_type.copied(),
 						peg_type: peg.0,
@@ -176,6 +185,7 @@ fn ball_ball_collide(
 		let offset = (transform_a.translation - transform_b.translation).xy();
 		if offset.length_squared() <= 32.0 * 32.0 {
 			let relative_velocity = velocity_a.0 - velocity_b.0;
+			let speed = Vec2::dot(relative_velocity, offset.normalize()).abs();
 			let dp = offset*Vec2::dot(relative_velocity, offset) / ((offset.length_squared()));
 
 			velocity_a.0 -= dp * bounciness * time.delta_seconds();
@@ -186,6 +196,7 @@ fn ball_ball_collide(
 			transform_b.translation -= push.extend(0.0);
 			collision_events.send(BallCollisionEvent {
 				pos: 0.5 * (transform_a.translation + transform_b.translation).xy(),
+				speed,
 				collision_type: BallCollisionEventType::Ball,
 			});
 		}
@@ -197,7 +208,7 @@ pub fn ball_target_collide(
 	target_query: Query<(Entity, &Transform, &Target)>,
 	mut events: EventWriter<BallTargetHit>
 ) {
-	for (_entity, &transform, &drop_type) in ball_query.iter_mut() {
+	for (entity, &transform, &drop_type) in ball_query.iter_mut() {
 		if transform.translation.y <= 0.0 {
 			//println!("Ball hit the bottom!");
 			for (target_entity, &target_transform, &target) in target_query.iter() {
@@ -205,7 +216,7 @@ pub fn ball_target_collide(
 				//println!("Distance: {}", distance);
 				if distance < target.radius {
 					//println!("Ball event sent!");
-					events.send(BallTargetHit { ball_type: drop_type, target: target_entity });
+					events.send(BallTargetHit { ball_type: drop_type, ball: entity, target: target_entity });
 				}
 			}
 		}
@@ -1,10 +1,9 @@
 use bevy::prelude::*;
-use bevy_pkv::PkvStore;
 use serde::{Serialize, Deserialize};
 
-use crate::setup::{despawn_entities_with, UserData, SaveData};
+use crate::{setup::{despawn_entities_with, UserData}, save::SaveGame, settings::Settings, menu::options::OptionsState};
 
-use super::{PachinkoSystemSet, targets::ClearOnDayTransition, minigames::parlor::PrizeLaunchTimer};
+use super::{PachinkoSystemSet, targets::ClearOnDayTransition, minigames::fever::Fever, calendar::Calendar};
 
 pub const DAY_LENGTH: f32 = 75.0;
 
@@ -15,18 +14,9 @@ pub struct StatesPlugin;
 
 impl Plugin for StatesPlugin {
 	fn build(&self, app: &mut App) {
-		let initialization_systems = (
-			initialise_clock,
-			initialise_wallet,
-			initialise_stress,
-			initialise_royal,
-		);
 		app
-			.add_system(toggle_pause_state.run_if(in_state(GameState::Game)))
-
-			.add_systems(initialization_systems.in_schedule(OnEnter(DayState::Morning)))
-			.add_systems(initialization_systems.in_schedule(OnEnter(DayState::Evening)))
-			.add_systems(initialization_systems.in_schedule(OnEnter(DayState::Night)))
+			.init_resource::<Calendar>()
+			.add_system(toggle_pause_state.run_if(in_state(GameState::Game)).run_if(in_state(OptionsState::Closed)))
 
 			.add_system(initialise_money_timer.in_schedule(OnEnter(DayState::Night)))
 
@@ -35,15 +25,12 @@ impl Plugin for StatesPlugin {
 			.add_system(advance_time.in_set(PachinkoSystemSet).after(initialise_clock))
 
 			.add_system(despawn_entities_with::<ClearOnDayTransition>.in_schedule(OnExit(GameState::Game)))
-			.add_system(despawn_entities_with::<ClearOnDayTransition>.in_schedule(OnExit(DayState::Morning)))
-			.add_system(despawn_entities_with::<ClearOnDayTransition>.in_schedule(OnExit(DayState::Evening)))
-			.add_system(despawn_entities_with::<ClearOnDayTransition>.in_schedule(OnExit(DayState::Night)))
 			;
 	}
 }
 
 // Game State
-#[derive(States, Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+#[derive(States, Debug, Clone, Copy, Eq, PartialEq, Hash, Default, Deserialize)]
 pub enum GameState {
 	#[default]
 	Boot,
@@ -348,45 +335,32 @@ pub fn advance_time(
 	mut day_timer: ResMut<DayTimer>,
 	mut next_day_state: ResMut<NextState<DayState>>,
 	mut next_pause_state: ResMut<NextState<PauseState>>,
-	mut pkv: ResMut<PkvStore>,
+	mut save_events: EventWriter<SaveGame>,
 	mut user_data: ResMut<UserData>,
-	prize_launch_timer: Res<PrizeLaunchTimer>,
+	fever: Res<Fever>,
+	calendar: Res<Calendar>,
 ) {
 	day_timer.0.tick(time.delta());
-	if !(prize_launch_timer.0.percent() > 0.0) {
+	// The clock waits for a fever to finish
+	if !fever.active() {
 		if day_timer.0.finished() {
-			let next = match current_day_state.0 {
-				DayState::Dawn => DayState::Morning,
-				DayState::Morning => DayState::Evening,
-				DayState::Evening => DayState::Night,
-				DayState::Night => DayState::Morning,
-			};
-			if let Ok(mut save_data) = pkv.get::<SaveData>("user_info") {
-				// Save progress here
-				save_data.money = user_data.money;
-				save_data.stress = user_data.stress;
-				save_data.flirt = user_data.flirt;
-				save_data.royal = user_data.royal;
-				save_data.drugs_taken = user_data.drugs_taken;
-
-				if next == DayState::Morning {
-					user_data.day = user_data.day + 1.0;
-					save_data.day = user_data.day;
-					if save_data.royal > 50.0 {
-						save_data.royal -= 50.0;
-						user_data.royal -= 50.0;
-					} else if save_data.royal > 20.0 {
-						save_data.royal -= 10.0;
-						user_data.royal -= 10.0;
-					}
+			let (next_day, next) = calendar.next_slot(user_data.day, current_day_state.0);
+			// Save progress here
+			if next_day != user_data.day {
+				user_data.day = next_day;
+				if user_data.royal > 50.0 {
+					user_data.royal -= 50.0;
+				} else if user_data.royal > 20.0 {
+					user_data.royal -= 10.0;
+				}
+				if calendar.rent_due(next_day) {
+					user_data.money = (user_data.money - calendar.rent).max(0.0);
 				}
-				user_data.time = next;
-				save_data.time = next;
-				user_data.lvl_init = false;
-				save_data.lvl_init = false;
-				pkv.set("user_info", &save_data)
-					.expect("Unable to store user");
 			}
+			user_data.time = next;
+			user_data.level = None;
+			user_data.lvl_init = false;
+			save_events.send(SaveGame);
 			next_day_state.set(next);
 			next_pause_state.set(PauseState::Paused);
 		}
@@ -395,10 +369,11 @@ pub fn advance_time(
 
 pub fn toggle_pause_state(
 	keyboard: Res<Input<KeyCode>>,
+	settings: Res<Settings>,
 	state: Res<State<PauseState>>,
 	mut next_state: ResMut<NextState<PauseState>>,
 ) {
-	if keyboard.just_pressed(KeyCode::P) {
+	if keyboard.just_pressed(settings.key_bindings.pause) {
 		if state.0 != PauseState::Unpaused {
 			next_state.set(PauseState::Unpaused);
 			println!("Pause State Unpaused");
@@ -1,8 +1,7 @@
 use bevy::{prelude::*, asset::LoadState};
 use bevy_kira_audio::prelude::*;
-use bevy_pkv::PkvStore;
 
-use crate::{game::{states::{GameState, PauseState, DayState}, targets::ClearOnDayTransition}, setup::{SaveData, UserData}};
+use crate::{audio::channels::VoiceChannel, game::{states::{GameState, PauseState, DayState}, targets::ClearOnDayTransition}, setup::UserData, save::SaveGame};
 
 #[derive(Clone)]
 struct CutsceneDescriptor<'a> {
@@ -126,7 +125,7 @@ impl PlayingCutscene {
 		self.caption = None;
 	}
 
-	pub fn start(&mut self, slide_transform: Transform, caption_transform: Transform, audio: Res<Audio>, commands: &mut Commands) {
+	pub fn start(&mut self, slide_transform: Transform, caption_transform: Transform, audio: Res<AudioChannel<VoiceChannel>>, commands: &mut Commands) {
 		let slide = self.cutscene.slides.first().expect("Some deadass made a presentation without slides!!!");
 		self.audio = Some(audio.play(slide.audio.clone()).handle());
 		self.slide = Some(commands.spawn((
@@ -148,7 +147,7 @@ impl PlayingCutscene {
 		self.timer = Timer::from_seconds(slide.captions.first().unwrap().duration, TimerMode::Once);
 	}
 
-	pub fn advance_slide(&mut self, mut images: Query<&mut Handle<Image>, With<Sprite>>, audio: Res<Audio>, mut audio_instances: ResMut<Assets<AudioInstance>>, commands: &mut Commands) -> bool {
+	pub fn advance_slide(&mut self, mut images: Query<&mut Handle<Image>, With<Sprite>>, audio: Res<AudioChannel<VoiceChannel>>, mut audio_instances: ResMut<Assets<AudioInstance>>, commands: &mut Commands) -> bool {
 		let current_slide = self.cutscene.slides.get(self.current_slide).unwrap().clone();
 		if self.current_caption + 1 >= current_slide.captions.len() {
 			//Next slide
@@ -204,7 +203,7 @@ fn load_cutscene(
 fn start_cutscene(
 	mut commands: Commands,
 	cutscene: Option<ResMut<PlayingCutscene>>,
-	audio: Res<Audio>,
+	audio: Res<AudioChannel<VoiceChannel>>,
 	asset_server: Res<AssetServer>,
 ) {
 	if let Some(mut pc) = cutscene {
@@ -220,14 +219,14 @@ fn start_cutscene(
 fn play_cutscene(
 	mut commands: Commands,
 	cutscene: Option<ResMut<PlayingCutscene>>,
-	audio: Res<Audio>,
+	audio: Res<AudioChannel<VoiceChannel>>,
 	audio_instances: ResMut<Assets<AudioInstance>>,
 	images: Query<&mut Handle<Image>, With<Sprite>>,
 	time: Res<Time>,
 	mut next_g_state: ResMut<NextState<GameState>>,
 	mut next_p_state: ResMut<NextState<PauseState>>,
 	mut next_d_state: ResMut<NextState<DayState>>,
-	mut pkv: ResMut<PkvStore>,
+	mut save_events: EventWriter<SaveGame>,
 	mut user_data: ResMut<UserData>,
 //	asset_server: Res<AssetServer>,
 ) {
@@ -242,28 +241,9 @@ fn play_cutscene(
 					next_p_state.set(next_states.1);
 					next_d_state.set(next_states.2);
 
-					let save_data = SaveData {
-						name: "BevyEnjoyer123".to_string(),
-						money: 30.0,
-						stress: 0.0,
-						flirt: 50.0,
-						royal: 0.0,
-						drugs_taken: 0.0,
-						day: 0.0,
-						time: DayState::Night,
-						lvl_init: false,
-					};
-					pkv.set("user_info", &save_data)
-						.expect("Unable to store user");
-		
-					user_data.name = save_data.name;
-					user_data.money = save_data.money;
-					user_data.stress = save_data.stress;
-					user_data.flirt = save_data.flirt;
-					user_data.royal = save_data.royal;
-					user_data.day = save_data.day;
-					user_data.time = save_data.time;
-					user_data.lvl_init = false;
+					let name = user_data.name.clone();
+					*user_data = UserData::new_game(name);
+					save_events.send(SaveGame);
 				}
 			}
 		}
@@ -1,7 +1,6 @@
 use bevy::prelude::*;
-use bevy_pkv::PkvStore;
 
-use crate::{game::states::{GameState, PauseState, DayState}, setup::{SaveData, UserData}};
+use crate::{game::states::{GameState, PauseState}, setup::UserData, save::SaveGame};
 
 pub struct SlideshowPlugin;
 
@@ -52,7 +51,7 @@ fn display_slideshow(
 	mut slideshow_query: Query<(Entity, With<Slideshow>)>,
 	mut next_game_state: ResMut<NextState<GameState>>,
 	mut next_pause_state: ResMut<NextState<PauseState>>,
-	mut pkv: ResMut<PkvStore>,
+	mut save_events: EventWriter<SaveGame>,
 	mut user_data: ResMut<UserData>,
 	current_slide: Res<CurrentSlide>,
 	asset_server: Res<AssetServer>,
@@ -72,28 +71,9 @@ fn display_slideshow(
 				)
 			);
 		} else {
-			let save_data = SaveData {
-				name: "BevyEnjoyer123".to_string(),
-				money: 30.0,
-				stress: 0.0,
-				flirt: 50.0,
-				royal: 0.0,
-				drugs_taken: 0.0,
-				day: 0.0,
-				time: DayState::Night,
-				lvl_init: false,
-			};
-			pkv.set("user_info", &save_data)
-				.expect("Unable to store user");
-
-			user_data.name = save_data.name;
-			user_data.money = save_data.money;
-			user_data.stress = save_data.stress;
-			user_data.flirt = save_data.flirt;
-			user_data.royal = save_data.royal;
-			user_data.day = save_data.day;
-			user_data.time = save_data.time;
-			user_data.lvl_init = false;
+			let name = user_data.name.clone();
+			*user_data = UserData::new_game(name);
+			save_events.send(SaveGame);
 
 			next_game_state.set(GameState::Game);
 			next_pause_state.set(PauseState::Paused);
@@ -1,11 +1,13 @@
 use bevy::{prelude::*, render::view::RenderLayers};
 
-use crate::{game::{states::{PauseState, DayState}, targets::ClearOnDayTransition}, setup::{despawn_entities_with, UserData}, menu::components::QuitGameButton};
+use crate::{game::{states::{PauseState, DayState}, targets::ClearOnDayTransition, calendar::Calendar, minigames::Minigames}, setup::{despawn_entities_with, UserData}, menu::components::{QuitGameButton, OptionsButton, ButtonInteraction}};
 
 pub const IDLE_BUTTON_COLOR: Color = Color::BLUE;
 pub const HOVERED_BUTTON_COLOR: Color = Color::PINK;
 pub const CLICKED_BUTTON_COLOR: Color = Color::ORANGE;
 
+const TUTORIAL_LAYERS: usize = 6;
+
 pub struct TutorialPlugin;
 
 impl Plugin for TutorialPlugin {
@@ -29,9 +31,7 @@ struct Tutorial;
 pub struct ConfirmButton;
 
 pub fn confirm_button_interaction(
-	mut button_query: Query<
-	(&Interaction, &mut BackgroundColor), 
-	(Changed<Interaction>, With<ConfirmButton>)>,
+	mut button_query: ButtonInteraction<ConfirmButton>,
 	current_day_state: Res<State<DayState>>,
 	mut next_day_state: ResMut<NextState<DayState>>,
 	mut next_pause_state: ResMut<NextState<PauseState>>,
@@ -61,17 +61,16 @@ pub fn spawn_popup(
 	mut commands: Commands,
 	asset_server: Res<AssetServer>,
 	user_data: Res<UserData>,
+	calendar: Res<Calendar>,
+	minigames: Res<Minigames>,
 ) {
-	let time = match user_data.time {
-		DayState::Dawn => String::from("Error"),
-		DayState::Morning => String::from("Work - Morning"),
-		DayState::Evening => String::from("Dinner - Evening"),
-		DayState::Night => String::from("Arcade - Night"),
-	};
+	let minigame = minigames.get(user_data.time);
+	let time = minigame.map_or("Error", |minigame| minigame.title);
 	let popup_text = if user_data.lvl_init {
 		String::from("Paused")
 	} else {
-		String::from(format!("{} - Day {}", time, user_data.day))
+		let rent = if calendar.rent_due(user_data.day) {" - Rent Day"} else {""};
+		String::from(format!("{} - {}, Day {}{}", time, calendar.day(user_data.day).name, user_data.day, rent))
 	};
 
 	let button_text = if user_data.lvl_init {
@@ -80,193 +79,20 @@ pub fn spawn_popup(
 
 		String::from("Begin")
 	};
-	match user_data.time {
-		DayState::Dawn => {	},
-		DayState::Morning => {
-			commands.spawn(
-				(
-					SpriteBundle {
-						transform: Transform::from_xyz(800.0, 450.0, -0.01),
-						texture: asset_server.load("backgrounds/work.png"),
-						..default()
-					},
-					Tutorial,
-				)
-			);
-			commands.spawn(
-				(
-					SpriteBundle {
-						transform: Transform::from_xyz(800.0, 450.0, -0.01),
-						texture: asset_server.load("backgrounds/work.png"),
-						..default()
-					},
-					Tutorial,
-				)
-			);
-			commands.spawn(
-				(
-					SpriteBundle {
-						transform: Transform::from_xyz(800.0, 450.0, -0.01),
-						texture: asset_server.load("backgrounds/work.png"),
-						..default()
-					},
-					Tutorial,
-				)
-			);
-			commands.spawn(
-				(
-					SpriteBundle {
-						transform: Transform::from_xyz(800.0, 450.0, -0.01),
-						texture: asset_server.load("backgrounds/work.png"),
-						..default()
-					},
-					Tutorial,
-				)
-			);
-			commands.spawn(
-				(
-					SpriteBundle {
-						transform: Transform::from_xyz(800.0, 450.0, -0.01),
-						texture: asset_server.load("backgrounds/work.png"),
-						..default()
-					},
-					Tutorial,
-				)
-			);
+	if let Some(minigame) = minigame {
+		// Drawn several times over so the translucent tutorial art comes out solid
+		for _ in 0..TUTORIAL_LAYERS {
 			commands.spawn(
 				(
 					SpriteBundle {
 						transform: Transform::from_xyz(800.0, 450.0, -0.01),
-						texture: asset_server.load("backgrounds/work.png"),
+						texture: asset_server.load(minigame.tutorial),
 						..default()
 					},
 					Tutorial,
 				)
 			);
-		},
-		DayState::Evening => {
-			commands.spawn(
-				(
-					SpriteBundle {
-						transform: Transform::from_xyz(800.0, 450.0, -0.01),
-						texture: asset_server.load("backgrounds/food.png"),
-						..default()
-					},
-					Tutorial,
-				)
-			);
-			commands.spawn(
-				(
-					SpriteBundle {
-						transform: Transform::from_xyz(800.0, 450.0, -0.01),
-						texture: asset_server.load("backgrounds/food.png"),
-						..default()
-					},
-					Tutorial,
-				)
-			);
-			commands.spawn(
-				(
-					SpriteBundle {
-						transform: Transform::from_xyz(800.0, 450.0, -0.01),
-						texture: asset_server.load("backgrounds/food.png"),
-						..default()
-					},
-					Tutorial,
-				)
-			);commands.spawn(
-				(
-					SpriteBundle {
-						transform: Transform::from_xyz(800.0, 450.0, -0.01),
-						texture: asset_server.load("backgrounds/food.png"),
-						..default()
-					},
-					Tutorial,
-				)
-			);
-			commands.spawn(
-				(
-					SpriteBundle {
-						transform: Transform::from_xyz(800.0, 450.0, -0.01),
-						texture: asset_server.load("backgrounds/food.png"),
-						..default()
-					},
-					Tutorial,
-				)
-			);
-			commands.spawn(
-				(
-					SpriteBundle {
-						transform: Transform::from_xyz(800.0, 450.0, -0.01),
-						texture: asset_server.load("backgrounds/food.png"),
-						..default()
-					},
-					Tutorial,
-				)
-			);
-		},
-		DayState::Night => {
-			commands.spawn(
-				(
-					SpriteBundle {
-						transform: Transform::from_xyz(800.0, 450.0, -0.01),
-						texture: asset_server.load("backgrounds/parlor.png"),
-						..default()
-					},
-					Tutorial,
-				)
-			);
-			commands.spawn(
-				(
-					SpriteBundle {
-						transform: Transform::from_xyz(800.0, 450.0, -0.01),
-						texture: asset_server.load("backgrounds/parlor.png"),
-						..default()
-					},
-					Tutorial,
-				)
-			);
-			commands.spawn(
-				(
-					SpriteBundle {
-						transform: Transform::from_xyz(800.0, 450.0, -0.01),
-						texture: asset_server.load("backgrounds/parlor.png"),
-						..default()
-					},
-					Tutorial,
-				)
-			);
-			commands.spawn(
-				(
-					SpriteBundle {
-						transform: Transform::from_xyz(800.0, 450.0, -0.01),
-						texture: asset_server.load("backgrounds/parlor.png"),
-						..default()
-					},
-					Tutorial,
-				)
-			);
-			commands.spawn(
-				(
-					SpriteBundle {
-						transform: Transform::from_xyz(800.0, 450.0, -0.01),
-						texture: asset_server.load("backgrounds/parlor.png"),
-						..default()
-					},
-					Tutorial,
-				)
-			);
-			commands.spawn(
-				(
-					SpriteBundle {
-						transform: Transform::from_xyz(800.0, 450.0, -0.01),
-						texture: asset_server.load("backgrounds/parlor.png"),
-						..default()
-					},
-					Tutorial,
-				)
-			);
-		},
+		}
 	}
 	let popup = build_popup(&mut commands, &asset_server, popup_text, button_text);
 	commands.entity(popup).insert(RenderLayers::layer((RenderLayers::TOTAL_LAYERS - 1) as u8));
@@ -323,7 +149,7 @@ commands
 							flex_direction: FlexDirection::Row,
 							justify_content: JustifyContent::Center,
 							align_items: AlignItems::Center,
-							size: Size::new(Val::Percent(30.0), Val::Percent(30.0)),
+							size: Size::new(Val::Percent(60.0), Val::Percent(30.0)),
 							gap: Size::new(Val::Px(16.0), Val::Px(16.0)),
 							..default()
 						},
@@ -368,6 +194,43 @@ commands
 							});
 						})
 					;
+					// Options Button
+					parent.spawn(
+						(
+							ButtonBundle {
+								style: Style {
+									justify_content: JustifyContent::Center,
+									align_items: AlignItems::Center,
+									size: Size::new(Val::Px(256.0), Val::Px(64.0)),
+									..default()
+								},
+								background_color: IDLE_BUTTON_COLOR.into(),
+								..default()
+							},
+							OptionsButton,
+						))
+						.with_children(|parent| {
+							// Options Button Text
+							parent.spawn(
+								TextBundle {
+									text: Text {
+										sections: vec![
+											TextSection::new(
+												"Options",
+												TextStyle {
+													font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+													font_size: 32.0,
+													color: Color::CYAN,
+												},
+											)
+										],
+										alignment: TextAlignment::Center,
+										..default()
+									},
+									..default()
+							});
+						})
+					;
 					// Quit Button
 					parent.spawn(
 						(
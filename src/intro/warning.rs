@@ -1,6 +1,15 @@
 use bevy::{prelude::*, asset::LoadState};
 
-use crate::game::states::GameState;
+use crate::{
+	game::states::GameState,
+	menu::{
+		components::{SafeModeButton, OptionLabel},
+		options::{OptionLabelKind, Rebinding, safe_mode_button_interaction, refresh_options},
+		styles::{IDLE_BUTTON_COLOR, get_button_text_style},
+	},
+	settings::Settings,
+	setup::despawn_entities_with,
+};
 
 pub struct WarningPlugin;
 
@@ -9,6 +18,12 @@ impl Plugin for WarningPlugin {
 		app
 		.add_system(show_warning.in_schedule(OnEnter(GameState::Boot)))
 		.add_system(handle_close_button.run_if(in_state(GameState::Boot)))
+		// Offered before anything flashy is shown, the same toggle as in the options
+		.add_systems((
+				safe_mode_button_interaction,
+				refresh_options.run_if(resource_changed::<Settings>()),
+			).distributive_run_if(in_state(GameState::Boot)))
+		.add_system(despawn_entities_with::<WarningDisclaimer>.in_schedule(OnExit(GameState::Boot)))
 		;
 	}
 }
@@ -19,6 +34,8 @@ struct WarningDisclaimer;
 fn show_warning(
 	mut commands: Commands,
 	asset_server: Res<AssetServer>,
+	settings: Res<Settings>,
+	rebinding: Res<Rebinding>,
 ) {
 	commands.spawn(
 		(
@@ -30,26 +47,73 @@ fn show_warning(
 			WarningDisclaimer,
 		)
 	);
+	commands
+		// Safe Mode Node Bundle
+		.spawn(
+			(NodeBundle {
+				style: Style {
+					position_type: PositionType::Absolute,
+					justify_content: JustifyContent::Center,
+					size: Size::new(Val::Percent(100.0), Val::Auto),
+					position: UiRect::bottom(Val::Px(32.0)),
+					..default()
+				},
+				..default()
+			},
+			WarningDisclaimer,
+		))
+		.with_children(|parent| {
+			// Safe Mode Button
+			parent.spawn(
+				(
+					ButtonBundle {
+						style: Style {
+							justify_content: JustifyContent::Center,
+							align_items: AlignItems::Center,
+							size: Size::new(Val::Px(512.0), Val::Px(64.0)),
+							..default()
+						},
+						background_color: IDLE_BUTTON_COLOR.into(),
+						..default()
+					},
+					SafeModeButton,
+				))
+				.with_children(|parent| {
+					// Safe Mode Button Text
+					parent.spawn(
+						(
+							TextBundle {
+								text: Text::from_section(
+									OptionLabelKind::SafeMode.text(&settings, &rebinding),
+									get_button_text_style(&asset_server),
+								).with_alignment(TextAlignment::Center),
+								..default()
+							},
+							OptionLabel(OptionLabelKind::SafeMode),
+						));
+				})
+			;
+		})
+	;
 }
 
 fn handle_close_button(
-	mut commands: Commands,
-	disclaimer: Query<(Entity, &Handle<Image>), With<WarningDisclaimer>>,
+	disclaimer: Query<&Handle<Image>, With<WarningDisclaimer>>,
+	safe_mode_button: Query<&Interaction, With<SafeModeButton>>,
 	asset_server: Res<AssetServer>,
 	mouse: Res<Input<MouseButton>>,
 	mut next_game_state: ResMut<NextState<GameState>>,
 ) {
 	let mut loaded = true;
-	for (_, image) in disclaimer.iter() {
+	for image in disclaimer.iter() {
 		if asset_server.get_load_state(image) != LoadState::Loaded {
 			loaded = false;
 		}
 	}
-	if loaded && mouse.just_released(MouseButton::Left) {
+	// Clicks on the safe mode button only toggle it
+	let on_button = safe_mode_button.iter().any(|interaction| *interaction != Interaction::None);
+	if loaded && !on_button && mouse.just_released(MouseButton::Left) {
 		next_game_state.set(GameState::Menu);
-		for (e, _) in disclaimer.iter() {
-			commands.entity(e).despawn_recursive();
-		}
 	}
 }
 
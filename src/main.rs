@@ -10,8 +10,10 @@ use intro::IntroPlugin;
 use debug::DebugPlugin;
 use game::GamePlugin;
 use menu::MenuPlugin;
-use audio::{music::{MusicPlugin, Volume}, sfx::SFXPlugin};
-use vfx::{post_processing::VFXPlugin, vfx_triggers::VFXTriggerPlugin};
+use save::SavePlugin;
+use settings::SettingsPlugin;
+use audio::{channels::ChannelsPlugin, music::MusicPlugin, sfx::SFXPlugin};
+use vfx::{particles::ParticlesPlugin, post_processing::VFXPlugin, vfx_triggers::VFXTriggerPlugin};
 
 use setup::*;
 
@@ -23,6 +25,8 @@ mod game;
 mod menu;
 mod audio;
 mod setup;
+mod save;
+mod settings;
 #[cfg(debug_assertions)]
 mod debug;
 mod vfx;
@@ -54,23 +58,21 @@ fn main() {
 		)
 		.add_plugin(VFXPlugin::default())
 		.add_plugin(VFXTriggerPlugin)
+		.add_plugin(ParticlesPlugin)
 		.add_plugin(AudioPlugin)
 		.add_state::<GameState>()
 		.add_state::<DayState>()
 		.add_state::<PauseState>()
 		.add_plugin(GamePlugin)
 		.add_plugin(MenuPlugin)
+		.add_plugin(ChannelsPlugin)
 		.add_plugin(MusicPlugin)
 		.add_plugin(SFXPlugin)
 		.add_plugin(IntroPlugin)
 		.insert_resource(PkvStore::new("InfiniteFallGames", "AHighStakesAffair"))
-		.insert_resource(Volume(1.0))
-		.insert_resource(UserData {
-			name: "".to_string(),
-			money: 0.0, stress: 0.0, flirt: 0.0, royal: 0.0,
-			unstable_royal: 0.0, drugs_taken: 0.0,
-			day: 0.0,	time: DayState::Dawn, lvl_init: false,
-		})
+		.add_plugin(SavePlugin)
+		.add_plugin(SettingsPlugin)
+		.init_resource::<UserData>()
 		// To ensure the image handle resource gets spawned before spawn_camera
 		.add_startup_systems((apply_system_buffers, spawn_camera).chain().after(vfx::post_processing::vfx_setup))
 		.add_startup_system(spawn_cursor)
@@ -1,4 +1,13 @@
-use bevy::prelude::Component;
+use bevy::prelude::{Component, Query, Interaction, BackgroundColor, Changed, With};
+
+use crate::{audio::channels::AudioBus, settings::KeyAction};
+
+use super::options::{SliderSetting, OptionLabelKind};
+
+// Buttons marked with T whose interaction changed this frame, with the colour to show it
+pub type ButtonInteraction<'w, 's, T> = Query<'w, 's,
+	(&'static Interaction, &'static mut BackgroundColor),
+	(Changed<Interaction>, With<T>)>;
 
 #[derive(Component)]
 pub struct Menu;
@@ -16,4 +25,71 @@ pub struct NewGameButton;
 pub struct LoadGameButton;
 
 #[derive(Component)]
-pub struct QuitGameButton;
\ No newline at end of file
+pub struct ExportSaveButton;
+
+#[derive(Component)]
+pub struct ImportSaveButton;
+
+#[derive(Component)]
+pub struct QuitGameButton;
+
+#[derive(Component)]
+pub struct SlotBrowser;
+
+#[derive(Component)]
+pub struct SlotButton(pub usize);
+
+#[derive(Component)]
+pub struct BackButton;
+
+#[derive(Component)]
+pub struct ConfirmOverwritePopup;
+
+#[derive(Component)]
+pub struct ConfirmOverwriteButton;
+
+#[derive(Component)]
+pub struct NameEntryPopup;
+
+#[derive(Component)]
+pub struct NameEntryText;
+
+#[derive(Component)]
+pub struct StartGameButton;
+
+#[derive(Component)]
+pub struct OptionsButton;
+
+#[derive(Component)]
+pub struct OptionsScreen;
+
+#[derive(Component)]
+pub struct CloseOptionsButton;
+
+#[derive(Component)]
+pub struct ReturnToMenuButton;
+
+#[derive(Component)]
+pub struct FullscreenButton;
+
+// Photosensitivity safe mode toggle
+#[derive(Component)]
+pub struct SafeModeButton;
+
+#[derive(Component)]
+pub struct RebindButton(pub KeyAction);
+
+// Mutes one audio bus without touching the others
+#[derive(Component)]
+pub struct MuteBusButton(pub AudioBus);
+
+// Track of a slider, clicking or dragging along it sets the value
+#[derive(Component)]
+pub struct OptionsSlider(pub SliderSetting);
+
+#[derive(Component)]
+pub struct OptionsSliderFill(pub SliderSetting);
+
+// Text which shows the current value of a setting
+#[derive(Component)]
+pub struct OptionLabel(pub OptionLabelKind);
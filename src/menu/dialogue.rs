@@ -1,15 +1,16 @@
 use bevy::prelude::*;
 
-use crate::{menu::{components::DialogueText, styles::get_vn_text_style}, setup::UserData, game::states::{DayState, DayTimer}};
+use crate::{menu::{components::DialogueText, styles::get_vn_text_style}, setup::UserData, game::{states::{DayState, DayTimer}, calendar::Calendar}};
 
 pub fn update_dialogue(
 	mut dialogue_query: Query<&mut Text, With<DialogueText>>,
 	asset_server: Res<AssetServer>,
 	user_data: Res<UserData>,
 	day_timer: Res<DayTimer>,
+	calendar: Res<Calendar>,
 ) {
 	let dialogue_line = if day_timer.0.percent() < 0.75 { 
-		 next_line(&user_data)
+		 next_line(&user_data, &calendar)
 	} else {"Uh oh, looks like it's almost time for you to go!".to_string()};
 	for mut text in dialogue_query.iter_mut() {
 		text.sections = vec![
@@ -23,7 +24,12 @@ pub fn update_dialogue(
 
 pub fn next_line(
 	user_info: &UserData,
+	calendar: &Calendar,
 ) -> String {
+	let schedule = calendar.day(user_info.day);
+	if let Some(line) = schedule.dialogue {
+		return line.to_string();
+	}
 	match user_info.time {
 		DayState::Dawn => "How did you even get here?".to_string(),
 		DayState::Morning => 
@@ -33,7 +39,7 @@ pub fn next_line(
 			if user_info.royal > 50.0 {"Now you're starting to see things my way! hahaHAHAHAHAHA".to_string()}
 			else {"Hey, nice soup you got there. Care to share? I've got a few ingredients of my own I could add...".to_string()},
 		DayState::Night => 
-			if (user_info.day % 2.0) == 1.0 {
+			if schedule.arcade == 1 {
 				if user_info.flirt > 50.0 {"Hey, why don't we get out of this place? I know a nice coffee shop nearby!".to_string()}
 				else {"Ah, so you met gramps? He's got some crazy theories. You'll probably see him again if you come back tomorrow.".to_string()}
 			} else {
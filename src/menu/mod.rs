@@ -1,30 +1,100 @@
 use bevy::prelude::*;
 
-use crate::{despawn_entities_with, game::states::{GameState, PauseState, DayState}};
+use crate::{despawn_entities_with, game::states::{GameState, PauseState, DayState}, save::transfer::SaveImported};
 
-use self::{systems::{layout::{spawn_menu, spawn_ui}, interactions::{new_game_button_interaction, load_game_button_interaction, quit_game_button_interaction}}, components::{Menu, Ui}, dialogue::update_dialogue};
+use self::{
+	systems::{
+		layout::{spawn_menu, spawn_ui, spawn_slot_browser, spawn_confirm_overwrite, spawn_name_entry},
+		interactions::{new_game_button_interaction, load_game_button_interaction, quit_game_button_interaction,
+			slot_button_interaction, back_button_interaction, confirm_overwrite_button_interaction,
+			start_game_button_interaction, type_profile_name, reset_menu_state,
+			export_save_button_interaction, import_save_button_interaction, transfer_slot_button_interaction,
+			show_imported_save}
+	},
+	components::{Menu, Ui, SlotBrowser, ConfirmOverwritePopup, NameEntryPopup},
+	dialogue::update_dialogue,
+	options::OptionsPlugin,
+};
 
 mod dialogue;
 mod systems;
-mod styles;
+pub mod styles;
 pub mod components;
+pub mod options;
+
+pub const MAX_NAME_LENGTH: usize = 16;
 
 pub struct MenuPlugin;
 
 impl Plugin for MenuPlugin {
     fn build(&self, app: &mut App) {
         app
+		.add_plugin(OptionsPlugin)
+		.add_state::<MenuState>()
+		.insert_resource(ProfileName(String::new()))
+		.init_resource::<ImportInto>()
 		.add_system(update_dialogue.run_if(not(in_state(DayState::Dawn))))
 		.add_system(spawn_menu.in_schedule(OnEnter(GameState::Menu)))
 		.add_system(spawn_ui.in_schedule(OnEnter(PauseState::Unpaused)))
 		.add_system(despawn_entities_with::<Menu>.in_schedule(OnExit(GameState::Menu)))
+		.add_system(reset_menu_state.in_schedule(OnExit(GameState::Menu)))
 		.add_system(despawn_entities_with::<Ui>.in_schedule(OnExit(GameState::Game)))
 		.add_system(despawn_entities_with::<Ui>.in_schedule(OnExit(PauseState::Unpaused)))
+		// Save slot browser
+		.add_system(spawn_slot_browser.in_schedule(OnEnter(MenuState::NewGame)))
+		.add_system(spawn_slot_browser.in_schedule(OnEnter(MenuState::LoadGame)))
+		.add_system(despawn_entities_with::<SlotBrowser>.in_schedule(OnExit(MenuState::NewGame)))
+		.add_system(despawn_entities_with::<SlotBrowser>.in_schedule(OnExit(MenuState::LoadGame)))
+		.add_system(spawn_slot_browser.in_schedule(OnEnter(MenuState::ExportSave)))
+		.add_system(spawn_slot_browser.in_schedule(OnEnter(MenuState::ImportSave)))
+		.add_system(despawn_entities_with::<SlotBrowser>.in_schedule(OnExit(MenuState::ExportSave)))
+		.add_system(despawn_entities_with::<SlotBrowser>.in_schedule(OnExit(MenuState::ImportSave)))
+		.add_system(spawn_confirm_overwrite.in_schedule(OnEnter(MenuState::ConfirmOverwrite)))
+		.add_system(despawn_entities_with::<ConfirmOverwritePopup>.in_schedule(OnExit(MenuState::ConfirmOverwrite)))
+		.add_system(spawn_name_entry.in_schedule(OnEnter(MenuState::NameEntry)))
+		.add_system(despawn_entities_with::<NameEntryPopup>.in_schedule(OnExit(MenuState::NameEntry)))
+		.add_system(type_profile_name.run_if(in_state(MenuState::NameEntry)))
+		.add_system(slot_button_interaction.run_if(not(transferring_saves)))
+		.add_system(transfer_slot_button_interaction.run_if(transferring_saves))
+		.add_system(show_imported_save.run_if(on_event::<SaveImported>()))
 		.add_systems((
 				new_game_button_interaction,
 				load_game_button_interaction,
+				export_save_button_interaction,
+				import_save_button_interaction,
 				quit_game_button_interaction,
+				back_button_interaction,
+				confirm_overwrite_button_interaction,
+				start_game_button_interaction,
 		))
 		;
     }
-}
\ No newline at end of file
+}
+
+// Which page of the main menu is open, the main buttons stay underneath the other pages
+#[derive(States, Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+pub enum MenuState {
+	#[default]
+	Main,
+	NewGame,
+	LoadGame,
+	ConfirmOverwrite,
+	NameEntry,
+	ExportSave,
+	ImportSave,
+}
+
+// The slot browser is open to move saves in or out of the game rather than to play them
+fn transferring_saves(
+	menu_state: Res<State<MenuState>>,
+) -> bool {
+	matches!(menu_state.0, MenuState::ExportSave | MenuState::ImportSave)
+}
+
+// Player entered profile name, typed in before starting a new game
+#[derive(Resource)]
+pub struct ProfileName(pub String);
+
+// Slot waiting on the player to confirm an import over its save, none while overwriting for a new game
+#[derive(Resource, Default)]
+pub struct ImportInto(pub Option<usize>);
@@ -0,0 +1,530 @@
+// Options screen, opened from the main menu or the pause popup and drawn over both
+
+use bevy::{prelude::*, ui::FocusPolicy, window::PrimaryWindow};
+
+use crate::{
+	audio::channels::AudioBus,
+	game::states::{GameState, DayState, PauseState},
+	settings::{Settings, KeyAction},
+	save::autosave::{AutosaveSet, TakeSnapshot},
+	despawn_entities_with,
+};
+
+use super::{
+	components::*,
+	styles::*,
+	systems::layout::build_button,
+};
+
+pub struct OptionsPlugin;
+
+impl Plugin for OptionsPlugin {
+	fn build(&self, app: &mut App) {
+		app
+		.add_state::<OptionsState>()
+		.insert_resource(Rebinding(None))
+		.add_system(spawn_options.in_schedule(OnEnter(OptionsState::Open)))
+		.add_system(despawn_entities_with::<OptionsScreen>.in_schedule(OnExit(OptionsState::Open)))
+		.add_system(stop_rebinding.in_schedule(OnExit(OptionsState::Open)))
+		.add_system(finish_return_to_menu
+			.in_schedule(OnEnter(DayState::Dawn))
+			.run_if(resource_exists::<ReturningToMenu>()))
+		.add_system(open_options_button_interaction)
+		.add_systems((
+				close_options_button_interaction,
+				fullscreen_button_interaction,
+				safe_mode_button_interaction,
+				mute_bus_button_interaction,
+				rebind_button_interaction,
+				drag_options_slider,
+				capture_rebind_key,
+			).distributive_run_if(in_state(OptionsState::Open)))
+		// Has to send its snapshot before the autosave systems run this frame, the board is gone by the next one
+		.add_system(return_to_menu_button_interaction
+			.before(AutosaveSet::Begin)
+			.run_if(in_state(OptionsState::Open)))
+		.add_system(refresh_options
+			.run_if(in_state(OptionsState::Open))
+			.run_if(resource_changed::<Settings>().or_else(resource_changed::<Rebinding>())))
+		;
+	}
+}
+
+#[derive(States, Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+pub enum OptionsState {
+	#[default]
+	Closed,
+	Open,
+}
+
+// Action waiting for its new key
+#[derive(Resource)]
+pub struct Rebinding(pub Option<KeyAction>);
+
+// Left the game through the options screen, the menu is entered once the level has been cleared away
+#[derive(Resource)]
+struct ReturningToMenu;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SliderSetting {
+	Volume(AudioBus),
+	VfxIntensity,
+}
+
+impl SliderSetting {
+	fn name(&self) -> &'static str {
+		match self {
+			SliderSetting::Volume(bus) => bus.name(),
+			SliderSetting::VfxIntensity => "Visual Effects",
+		}
+	}
+
+	fn get(&self, settings: &Settings) -> f32 {
+		match self {
+			SliderSetting::Volume(bus) => settings.volume(*bus) as f32,
+			SliderSetting::VfxIntensity => settings.vfx_intensity,
+		}
+	}
+
+	fn set(&self, settings: &mut Settings, value: f32) {
+		match self {
+			SliderSetting::Volume(bus) => settings.set_volume(*bus, value as f64),
+			SliderSetting::VfxIntensity => settings.vfx_intensity = value,
+		}
+	}
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OptionLabelKind {
+	Fullscreen,
+	SafeMode,
+	Mute(AudioBus),
+	Key(KeyAction),
+}
+
+impl OptionLabelKind {
+	pub fn text(&self, settings: &Settings, rebinding: &Rebinding) -> String {
+		match self {
+			OptionLabelKind::Fullscreen => format!("Fullscreen: {}", if settings.fullscreen {"On"} else {"Off"}),
+			OptionLabelKind::SafeMode => format!("Safe Mode: {}", if settings.safe_mode {"On"} else {"Off"}),
+			OptionLabelKind::Mute(bus) => if settings.bus_muted(*bus) {"Off"} else {"On"}.to_string(),
+			OptionLabelKind::Key(action) if rebinding.0 == Some(*action) => format!("{}: press a key", action.name()),
+			OptionLabelKind::Key(action) => format!("{}: {:?}", action.name(), settings.key_bindings.get(*action)),
+		}
+	}
+}
+
+fn spawn_options(
+	mut commands: Commands,
+	asset_server: Res<AssetServer>,
+	settings: Res<Settings>,
+	rebinding: Res<Rebinding>,
+	game_state: Res<State<GameState>>,
+) {
+	commands
+		// Options Node Bundle
+		.spawn(
+			(NodeBundle {
+				style: OVERLAY_STYLE,
+				background_color: OVERLAY_COLOR.into(),
+				// Above the slot browser and pause popup, and stops clicks reaching them
+				z_index: ZIndex::Global(20),
+				focus_policy: FocusPolicy::Block,
+				..default()
+			},
+			OptionsScreen,
+		))
+		.with_children(|parent| {
+			// Options Title
+			parent.spawn(
+				TextBundle {
+					text: Text::from_section("Options", get_button_text_style(&asset_server))
+						.with_alignment(TextAlignment::Center),
+					..default()
+			});
+			for slider in [
+				SliderSetting::Volume(AudioBus::Music),
+				SliderSetting::Volume(AudioBus::Sfx),
+				SliderSetting::Volume(AudioBus::Voice),
+				SliderSetting::VfxIntensity,
+			] {
+				build_slider(parent, &asset_server, &settings, slider);
+			}
+			// Two buttons to a row so everything fits on screen
+			let rows = [
+				[OptionLabelKind::Fullscreen, OptionLabelKind::SafeMode],
+				[OptionLabelKind::Key(KeyAction::Pause), OptionLabelKind::Key(KeyAction::Mute)],
+				[OptionLabelKind::Key(KeyAction::MuteMusic), OptionLabelKind::Key(KeyAction::MuteSfx)],
+			];
+			for row in rows {
+				parent.spawn(
+					NodeBundle {
+						style: Style {
+							flex_direction: FlexDirection::Row,
+							gap: Size::new(Val::Px(16.0), Val::Px(16.0)),
+							..default()
+						},
+						..default()
+					})
+					.with_children(|parent| {
+						for label in row {
+							let text = label.text(&settings, &rebinding);
+							match label {
+								OptionLabelKind::Fullscreen => build_option_button(parent, &asset_server, text, label, FullscreenButton),
+								OptionLabelKind::SafeMode => build_option_button(parent, &asset_server, text, label, SafeModeButton),
+								OptionLabelKind::Key(action) => build_option_button(parent, &asset_server, text, label, RebindButton(action)),
+								OptionLabelKind::Mute(bus) => build_option_button(parent, &asset_server, text, label, MuteBusButton(bus)),
+							}
+						}
+					})
+				;
+			}
+			if game_state.0 == GameState::Game {
+				build_button(parent, &asset_server, "Main Menu", ReturnToMenuButton);
+			}
+			build_button(parent, &asset_server, "Back", CloseOptionsButton);
+		});
+}
+
+fn build_slider(
+	parent: &mut ChildBuilder,
+	asset_server: &Res<AssetServer>,
+	settings: &Settings,
+	slider: SliderSetting,
+) {
+	let value = slider.get(settings);
+	// Slider Row
+	parent.spawn(
+		NodeBundle {
+			style: Style {
+				flex_direction: FlexDirection::Row,
+				justify_content: JustifyContent::Center,
+				align_items: AlignItems::Center,
+				gap: Size::new(Val::Px(16.0), Val::Px(16.0)),
+				..default()
+			},
+			..default()
+		})
+		.with_children(|parent| {
+			// Slider Label
+			parent.spawn(
+				TextBundle {
+					style: Style {
+						size: Size::new(Val::Px(224.0), Val::Auto),
+						..default()
+					},
+					text: Text::from_section(slider.name(), get_slot_text_style(asset_server)),
+					..default()
+			});
+			// Slider Track
+			parent.spawn(
+				(
+					ButtonBundle {
+						style: SLIDER_STYLE,
+						background_color: DISABLED_BUTTON_COLOR.into(),
+						..default()
+					},
+					OptionsSlider(slider),
+				))
+				.with_children(|parent| {
+					// Slider Fill
+					parent.spawn(
+						(
+							NodeBundle {
+								style: Style {
+									size: Size::new(Val::Percent(value * 100.0), Val::Percent(100.0)),
+									..default()
+								},
+								background_color: IDLE_BUTTON_COLOR.into(),
+								focus_policy: FocusPolicy::Pass,
+								..default()
+							},
+							OptionsSliderFill(slider),
+						));
+				})
+			;
+			match slider {
+				SliderSetting::Volume(bus) => {
+					let label = OptionLabelKind::Mute(bus);
+					// Mute Toggle
+					parent.spawn(
+						(
+							ButtonBundle {
+								style: MUTE_BUTTON_STYLE,
+								background_color: IDLE_BUTTON_COLOR.into(),
+								..default()
+							},
+							MuteBusButton(bus),
+						))
+						.with_children(|parent| {
+							parent.spawn(
+								(
+									TextBundle {
+										text: Text::from_section(label.text(settings, &Rebinding(None)), get_slot_text_style(asset_server)),
+										..default()
+									},
+									OptionLabel(label),
+								));
+						})
+					;
+				},
+				// Keeps the track lined up with the volume ones
+				SliderSetting::VfxIntensity => {
+					parent.spawn(
+						NodeBundle {
+							style: MUTE_BUTTON_STYLE,
+							..default()
+						});
+				},
+			}
+		})
+	;
+}
+
+fn build_option_button(
+	parent: &mut ChildBuilder,
+	asset_server: &Res<AssetServer>,
+	text: String,
+	label: OptionLabelKind,
+	marker: impl Component,
+) {
+	parent.spawn(
+		(
+			ButtonBundle {
+				style: OPTION_BUTTON_STYLE,
+				background_color: IDLE_BUTTON_COLOR.into(),
+				..default()
+			},
+			marker,
+		))
+		.with_children(|parent| {
+			parent.spawn(
+				(
+					TextBundle {
+						text: Text::from_section(text, get_button_text_style(asset_server))
+							.with_alignment(TextAlignment::Center),
+						..default()
+					},
+					OptionLabel(label),
+				));
+		})
+	;
+}
+
+pub fn refresh_options(
+	settings: Res<Settings>,
+	rebinding: Res<Rebinding>,
+	mut fill_query: Query<(&mut Style, &OptionsSliderFill)>,
+	mut label_query: Query<(&mut Text, &OptionLabel)>,
+) {
+	for (mut style, fill) in fill_query.iter_mut() {
+		style.size.width = Val::Percent(fill.0.get(&settings) * 100.0);
+	}
+	for (mut text, label) in label_query.iter_mut() {
+		text.sections[0].value = label.0.text(&settings, &rebinding);
+	}
+}
+
+pub fn open_options_button_interaction(
+	mut button_query: ButtonInteraction<OptionsButton>,
+	mut next_options_state: ResMut<NextState<OptionsState>>,
+) {
+	for (interaction, mut background_color) in button_query.iter_mut() {
+		match *interaction {
+			Interaction::Clicked => {
+				*background_color = CLICKED_BUTTON_COLOR.into();
+				next_options_state.set(OptionsState::Open);
+			}
+			Interaction::Hovered => {
+				*background_color = HOVERED_BUTTON_COLOR.into();
+			}
+			Interaction::None => {
+				*background_color = IDLE_BUTTON_COLOR.into();
+			}
+		}
+	}
+}
+
+pub fn close_options_button_interaction(
+	mut button_query: ButtonInteraction<CloseOptionsButton>,
+	mut next_options_state: ResMut<NextState<OptionsState>>,
+) {
+	if let Ok((interaction, mut background_color)) = button_query.get_single_mut() {
+		match *interaction {
+			Interaction::Clicked => {
+				*background_color = CLICKED_BUTTON_COLOR.into();
+				next_options_state.set(OptionsState::Closed);
+			}
+			Interaction::Hovered => {
+				*background_color = HOVERED_BUTTON_COLOR.into();
+			}
+			Interaction::None => {
+				*background_color = IDLE_BUTTON_COLOR.into();
+			}
+		}
+	}
+}
+
+pub fn fullscreen_button_interaction(
+	mut button_query: ButtonInteraction<FullscreenButton>,
+	mut settings: ResMut<Settings>,
+) {
+	if let Ok((interaction, mut background_color)) = button_query.get_single_mut() {
+		match *interaction {
+			Interaction::Clicked => {
+				*background_color = CLICKED_BUTTON_COLOR.into();
+				settings.fullscreen = !settings.fullscreen;
+			}
+			Interaction::Hovered => {
+				*background_color = HOVERED_BUTTON_COLOR.into();
+			}
+			Interaction::None => {
+				*background_color = IDLE_BUTTON_COLOR.into();
+			}
+		}
+	}
+}
+
+pub fn safe_mode_button_interaction(
+	mut button_query: ButtonInteraction<SafeModeButton>,
+	mut settings: ResMut<Settings>,
+) {
+	if let Ok((interaction, mut background_color)) = button_query.get_single_mut() {
+		match *interaction {
+			Interaction::Clicked => {
+				*background_color = CLICKED_BUTTON_COLOR.into();
+				settings.safe_mode = !settings.safe_mode;
+			}
+			Interaction::Hovered => {
+				*background_color = HOVERED_BUTTON_COLOR.into();
+			}
+			Interaction::None => {
+				*background_color = IDLE_BUTTON_COLOR.into();
+			}
+		}
+	}
+}
+
+pub fn mute_bus_button_interaction(
+	mut button_query: Query<
+	(&Interaction, &mut BackgroundColor, &MuteBusButton),
+	Changed<Interaction>>,
+	mut settings: ResMut<Settings>,
+) {
+	for (interaction, mut background_color, mute_button) in button_query.iter_mut() {
+		match *interaction {
+			Interaction::Clicked => {
+				*background_color = CLICKED_BUTTON_COLOR.into();
+				settings.toggle_bus_mute(mute_button.0);
+			}
+			Interaction::Hovered => {
+				*background_color = HOVERED_BUTTON_COLOR.into();
+			}
+			Interaction::None => {
+				*background_color = IDLE_BUTTON_COLOR.into();
+			}
+		}
+	}
+}
+
+pub fn rebind_button_interaction(
+	mut button_query: Query<
+	(&Interaction, &mut BackgroundColor, &RebindButton),
+	Changed<Interaction>>,
+	mut rebinding: ResMut<Rebinding>,
+) {
+	for (interaction, mut background_color, rebind_button) in button_query.iter_mut() {
+		match *interaction {
+			Interaction::Clicked => {
+				*background_color = CLICKED_BUTTON_COLOR.into();
+				rebinding.0 = Some(rebind_button.0);
+			}
+			Interaction::Hovered => {
+				*background_color = HOVERED_BUTTON_COLOR.into();
+			}
+			Interaction::None => {
+				*background_color = IDLE_BUTTON_COLOR.into();
+			}
+		}
+	}
+}
+
+// Escape cancels, any other key becomes the new binding
+fn capture_rebind_key(
+	keyboard: Res<Input<KeyCode>>,
+	mut rebinding: ResMut<Rebinding>,
+	mut settings: ResMut<Settings>,
+) {
+	if let Some(action) = rebinding.0 {
+		if let Some(&key) = keyboard.get_just_pressed().next() {
+			if key != KeyCode::Escape {
+				settings.key_bindings.set(action, key);
+			}
+			rebinding.0 = None;
+		}
+	}
+}
+
+fn stop_rebinding(
+	mut rebinding: ResMut<Rebinding>,
+) {
+	rebinding.0 = None;
+}
+
+// Sliders keep following the cursor for as long as they're held
+fn drag_options_slider(
+	slider_query: Query<(&Interaction, &Node, &GlobalTransform, &OptionsSlider)>,
+	window_query: Query<&Window, With<PrimaryWindow>>,
+	mut settings: ResMut<Settings>,
+) {
+	let Some(cursor) = window_query.get_single().ok().and_then(|window| window.cursor_position()) else {
+		return;
+	};
+	for (interaction, node, transform, slider) in slider_query.iter() {
+		if *interaction == Interaction::Clicked {
+			let left = transform.translation().x - node.size().x / 2.0;
+			let value = ((cursor.x - left) / node.size().x).clamp(0.0, 1.0);
+			if slider.0.get(&settings) != value {
+				slider.0.set(&mut settings, value);
+			}
+		}
+	}
+}
+
+pub fn return_to_menu_button_interaction(
+	mut commands: Commands,
+	mut button_query: ButtonInteraction<ReturnToMenuButton>,
+	mut snapshot_events: EventWriter<TakeSnapshot>,
+	mut next_options_state: ResMut<NextState<OptionsState>>,
+	mut next_day_state: ResMut<NextState<DayState>>,
+	mut next_pause_state: ResMut<NextState<PauseState>>,
+) {
+	if let Ok((interaction, mut background_color)) = button_query.get_single_mut() {
+		match *interaction {
+			Interaction::Clicked => {
+				*background_color = CLICKED_BUTTON_COLOR.into();
+				// Saves the board as it is, so loading the slot carries on from here
+				snapshot_events.send(TakeSnapshot);
+				next_options_state.set(OptionsState::Closed);
+				next_day_state.set(DayState::Dawn);
+				next_pause_state.set(PauseState::Initial);
+				commands.insert_resource(ReturningToMenu);
+			}
+			Interaction::Hovered => {
+				*background_color = HOVERED_BUTTON_COLOR.into();
+			}
+			Interaction::None => {
+				*background_color = IDLE_BUTTON_COLOR.into();
+			}
+		}
+	}
+}
+
+// Leaving the level and entering the menu in the same frame would let the level's music stop the menu's
+fn finish_return_to_menu(
+	mut commands: Commands,
+	mut next_game_state: ResMut<NextState<GameState>>,
+) {
+	next_game_state.set(GameState::Menu);
+	commands.remove_resource::<ReturningToMenu>();
+}
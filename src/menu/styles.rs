@@ -12,6 +12,45 @@ pub const BUTTON_STYLE: Style = Style {
 	..Style::DEFAULT
 };
 
+pub const SLOT_BUTTON_STYLE: Style = Style {
+	flex_direction: FlexDirection::Column,
+	justify_content: JustifyContent::Center,
+	align_items: AlignItems::Center,
+	size: Size::new(Val::Px(640.0), Val::Px(96.0)),
+	..Style::DEFAULT
+};
+
+pub const OPTION_BUTTON_STYLE: Style = Style {
+	justify_content: JustifyContent::Center,
+	align_items: AlignItems::Center,
+	size: Size::new(Val::Px(384.0), Val::Px(64.0)),
+	..Style::DEFAULT
+};
+
+pub const SLIDER_STYLE: Style = Style {
+	size: Size::new(Val::Px(384.0), Val::Px(32.0)),
+	..Style::DEFAULT
+};
+
+pub const MUTE_BUTTON_STYLE: Style = Style {
+	justify_content: JustifyContent::Center,
+	align_items: AlignItems::Center,
+	size: Size::new(Val::Px(96.0), Val::Px(32.0)),
+	..Style::DEFAULT
+};
+
+pub const OVERLAY_STYLE: Style = Style {
+	position_type: PositionType::Absolute,
+	flex_direction: FlexDirection::Column,
+	justify_content: JustifyContent::Center,
+	align_items: AlignItems::Center,
+	size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+	gap: Size::new(Val::Px(16.0), Val::Px(16.0)),
+	..Style::DEFAULT
+};
+
+pub const OVERLAY_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.85);
+
 pub fn get_title_text_style(
 	asset_server: &Res<AssetServer>
 ) -> TextStyle {
@@ -32,6 +71,16 @@ pub fn get_button_text_style(
 	}
 }
 
+pub fn get_slot_text_style(
+	asset_server: &Res<AssetServer>
+) -> TextStyle {
+	TextStyle {
+		font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+		font_size: 24.0,
+		color: Color::CYAN,
+	}
+}
+
 pub fn get_vn_text_style(
 	asset_server: &Res<AssetServer>
 ) -> TextStyle {
@@ -2,24 +2,22 @@ use bevy::app::AppExit;
 use bevy::prelude::*;
 use bevy_pkv::PkvStore;
 
-use crate::game::states::{DayState, PauseState};
+use crate::save::{CurrentSlot, LoadGame, read_slot, any_slot_used, format::SaveError, transfer::{ExportSave, ImportSave}};
 
-use super::super::super::setup::{SaveData, UserData};
-use super::super::GameState;
+use super::super::super::setup::UserData;
+use super::super::{GameState, MenuState, ProfileName, ImportInto, MAX_NAME_LENGTH};
 use super::super::components::*;
 use super::super::styles::{IDLE_BUTTON_COLOR, HOVERED_BUTTON_COLOR, CLICKED_BUTTON_COLOR, DISABLED_BUTTON_COLOR};
 
 pub fn new_game_button_interaction(
-	mut button_query: Query<
-	(&Interaction, &mut BackgroundColor), 
-	(Changed<Interaction>, With<NewGameButton>)>,
-	mut next_state: ResMut<NextState<GameState>>,
+	mut button_query: ButtonInteraction<NewGameButton>,
+	mut next_menu_state: ResMut<NextState<MenuState>>,
 ) {
 	if let Ok((interaction, mut background_color)) = button_query.get_single_mut() {
 		match *interaction {
 			Interaction::Clicked => {
 				*background_color = CLICKED_BUTTON_COLOR.into();
-				next_state.set(GameState::Intro);
+				next_menu_state.set(MenuState::NewGame);
 			}
 			Interaction::Hovered => {
 				*background_color = HOVERED_BUTTON_COLOR.into();
@@ -32,38 +30,41 @@ pub fn new_game_button_interaction(
 }
 
 pub fn load_game_button_interaction(
-	mut button_query: Query<
-	(&Interaction, &mut BackgroundColor), 
-	(Changed<Interaction>, With<LoadGameButton>)>,
-	mut next_game_state: ResMut<NextState<GameState>>,
-	mut next_day_state: ResMut<NextState<DayState>>,
-	mut next_pause_state: ResMut<NextState<PauseState>>,
+	mut button_query: ButtonInteraction<LoadGameButton>,
+	mut next_menu_state: ResMut<NextState<MenuState>>,
 	pkv: Res<PkvStore>,
-	mut user_data: ResMut<UserData>,
 ) {
 	if let Ok((interaction, mut background_color)) = button_query.get_single_mut() {
-		if let Ok(save_data) = pkv.get::<SaveData>("user_info") {
+		if any_slot_used(&pkv) {
 			match *interaction {
 				Interaction::Clicked => {
 					*background_color = CLICKED_BUTTON_COLOR.into();
+					next_menu_state.set(MenuState::LoadGame);
+				}
+				Interaction::Hovered => {
+					*background_color = HOVERED_BUTTON_COLOR.into();
+				}
+				Interaction::None => {
+					*background_color = IDLE_BUTTON_COLOR.into();
+				}
+			}
+		} else {
+			*background_color = DISABLED_BUTTON_COLOR.into();
+		}
+	}
+}
 
-					user_data.name = save_data.name;
-					user_data.money = save_data.money;
-					user_data.stress = save_data.stress;
-					user_data.flirt = save_data.flirt;
-					user_data.royal = save_data.royal;
-					user_data.day = save_data.day;
-					user_data.time = save_data.time;
-					user_data.lvl_init = false;
-
-					match user_data.time {
-						DayState::Dawn => (),
-						DayState::Morning => next_day_state.set(DayState::Morning),
-						DayState::Evening => next_day_state.set(DayState::Evening),
-						DayState::Night => next_day_state.set(DayState::Night),
-					}
-					next_game_state.set(GameState::Game);
-					next_pause_state.set(PauseState::Paused);
+pub fn export_save_button_interaction(
+	mut button_query: ButtonInteraction<ExportSaveButton>,
+	mut next_menu_state: ResMut<NextState<MenuState>>,
+	pkv: Res<PkvStore>,
+) {
+	if let Ok((interaction, mut background_color)) = button_query.get_single_mut() {
+		if any_slot_used(&pkv) {
+			match *interaction {
+				Interaction::Clicked => {
+					*background_color = CLICKED_BUTTON_COLOR.into();
+					next_menu_state.set(MenuState::ExportSave);
 				}
 				Interaction::Hovered => {
 					*background_color = HOVERED_BUTTON_COLOR.into();
@@ -78,10 +79,227 @@ pub fn load_game_button_interaction(
 	}
 }
 
-pub fn quit_game_button_interaction(
+pub fn import_save_button_interaction(
+	mut button_query: ButtonInteraction<ImportSaveButton>,
+	mut next_menu_state: ResMut<NextState<MenuState>>,
+) {
+	if let Ok((interaction, mut background_color)) = button_query.get_single_mut() {
+		match *interaction {
+			Interaction::Clicked => {
+				*background_color = CLICKED_BUTTON_COLOR.into();
+				next_menu_state.set(MenuState::ImportSave);
+			}
+			Interaction::Hovered => {
+				*background_color = HOVERED_BUTTON_COLOR.into();
+			}
+			Interaction::None => {
+				*background_color = IDLE_BUTTON_COLOR.into();
+			}
+		}
+	}
+}
+
+pub fn slot_button_interaction(
+	mut button_query: Query<
+	(&Interaction, &mut BackgroundColor, &SlotButton),
+	Changed<Interaction>>,
+	menu_state: Res<State<MenuState>>,
+	mut next_menu_state: ResMut<NextState<MenuState>>,
+	mut load_events: EventWriter<LoadGame>,
+	pkv: Res<PkvStore>,
+	mut current_slot: ResMut<CurrentSlot>,
+) {
+	for (interaction, mut background_color, slot_button) in button_query.iter_mut() {
+		let save_data = read_slot(&pkv, slot_button.0);
+		if menu_state.0 == MenuState::LoadGame && save_data.is_err() {
+			*background_color = DISABLED_BUTTON_COLOR.into();
+			continue;
+		}
+		match *interaction {
+			Interaction::Clicked => {
+				*background_color = CLICKED_BUTTON_COLOR.into();
+				current_slot.0 = slot_button.0;
+				match save_data {
+					Ok(_) if menu_state.0 == MenuState::LoadGame => load_events.send(LoadGame(slot_button.0)),
+					Err(SaveError::NotFound) => next_menu_state.set(MenuState::NameEntry),
+					_ => next_menu_state.set(MenuState::ConfirmOverwrite),
+				}
+			}
+			Interaction::Hovered => {
+				*background_color = HOVERED_BUTTON_COLOR.into();
+			}
+			Interaction::None => {
+				*background_color = IDLE_BUTTON_COLOR.into();
+			}
+		}
+	}
+}
+
+// Slot buttons while the browser is open for exporting or importing
+pub fn transfer_slot_button_interaction(
 	mut button_query: Query<
-	(&Interaction, &mut BackgroundColor), 
-	(Changed<Interaction>, With<QuitGameButton>)>,
+	(&Interaction, &mut BackgroundColor, &SlotButton),
+	Changed<Interaction>>,
+	menu_state: Res<State<MenuState>>,
+	mut next_menu_state: ResMut<NextState<MenuState>>,
+	mut export_events: EventWriter<ExportSave>,
+	mut import_events: EventWriter<ImportSave>,
+	mut import_into: ResMut<ImportInto>,
+	pkv: Res<PkvStore>,
+) {
+	for (interaction, mut background_color, slot_button) in button_query.iter_mut() {
+		let exporting = menu_state.0 == MenuState::ExportSave;
+		let save_data = read_slot(&pkv, slot_button.0);
+		if exporting && save_data.is_err() {
+			*background_color = DISABLED_BUTTON_COLOR.into();
+			continue;
+		}
+		match *interaction {
+			Interaction::Clicked => {
+				*background_color = CLICKED_BUTTON_COLOR.into();
+				match save_data {
+					_ if exporting => export_events.send(ExportSave(slot_button.0)),
+					Err(SaveError::NotFound) => import_events.send(ImportSave(slot_button.0)),
+					_ => {
+						import_into.0 = Some(slot_button.0);
+						next_menu_state.set(MenuState::ConfirmOverwrite);
+					}
+				}
+			}
+			Interaction::Hovered => {
+				*background_color = HOVERED_BUTTON_COLOR.into();
+			}
+			Interaction::None => {
+				*background_color = IDLE_BUTTON_COLOR.into();
+			}
+		}
+	}
+}
+
+// Imported saves are shown in the load browser, ready to be played
+pub fn show_imported_save(
+	mut next_menu_state: ResMut<NextState<MenuState>>,
+) {
+	next_menu_state.set(MenuState::LoadGame);
+}
+
+pub fn back_button_interaction(
+	mut button_query: ButtonInteraction<BackButton>,
+	menu_state: Res<State<MenuState>>,
+	mut next_menu_state: ResMut<NextState<MenuState>>,
+	mut import_into: ResMut<ImportInto>,
+) {
+	for (interaction, mut background_color) in button_query.iter_mut() {
+		match *interaction {
+			Interaction::Clicked => {
+				*background_color = CLICKED_BUTTON_COLOR.into();
+				let previous = match menu_state.0 {
+					MenuState::ConfirmOverwrite if import_into.0.take().is_some() => MenuState::ImportSave,
+					MenuState::ConfirmOverwrite | MenuState::NameEntry => MenuState::NewGame,
+					_ => MenuState::Main,
+				};
+				next_menu_state.set(previous);
+			}
+			Interaction::Hovered => {
+				*background_color = HOVERED_BUTTON_COLOR.into();
+			}
+			Interaction::None => {
+				*background_color = IDLE_BUTTON_COLOR.into();
+			}
+		}
+	}
+}
+
+pub fn confirm_overwrite_button_interaction(
+	mut button_query: ButtonInteraction<ConfirmOverwriteButton>,
+	mut next_menu_state: ResMut<NextState<MenuState>>,
+	mut import_events: EventWriter<ImportSave>,
+	mut import_into: ResMut<ImportInto>,
+) {
+	if let Ok((interaction, mut background_color)) = button_query.get_single_mut() {
+		match *interaction {
+			Interaction::Clicked => {
+				*background_color = CLICKED_BUTTON_COLOR.into();
+				// The import browser stays open until the picked file has been written
+				if let Some(slot) = import_into.0.take() {
+					import_events.send(ImportSave(slot));
+					next_menu_state.set(MenuState::ImportSave);
+				} else {
+					next_menu_state.set(MenuState::NameEntry);
+				}
+			}
+			Interaction::Hovered => {
+				*background_color = HOVERED_BUTTON_COLOR.into();
+			}
+			Interaction::None => {
+				*background_color = IDLE_BUTTON_COLOR.into();
+			}
+		}
+	}
+}
+
+pub fn start_game_button_interaction(
+	mut button_query: ButtonInteraction<StartGameButton>,
+	profile_name: Res<ProfileName>,
+	mut next_game_state: ResMut<NextState<GameState>>,
+	mut user_data: ResMut<UserData>,
+) {
+	if let Ok((interaction, mut background_color)) = button_query.get_single_mut() {
+		if profile_name.0.trim().is_empty() {
+			*background_color = DISABLED_BUTTON_COLOR.into();
+			return;
+		}
+		match *interaction {
+			Interaction::Clicked => {
+				*background_color = CLICKED_BUTTON_COLOR.into();
+				user_data.name = profile_name.0.trim().to_string();
+				next_game_state.set(GameState::Intro);
+			}
+			Interaction::Hovered => {
+				*background_color = HOVERED_BUTTON_COLOR.into();
+			}
+			Interaction::None => {
+				*background_color = IDLE_BUTTON_COLOR.into();
+			}
+		}
+	}
+}
+
+pub fn type_profile_name(
+	mut characters: EventReader<ReceivedCharacter>,
+	keyboard: Res<Input<KeyCode>>,
+	mut profile_name: ResMut<ProfileName>,
+	mut name_text_query: Query<&mut Text, With<NameEntryText>>,
+	mut next_game_state: ResMut<NextState<GameState>>,
+	mut user_data: ResMut<UserData>,
+) {
+	for event in characters.iter() {
+		if !event.char.is_control() && profile_name.0.chars().count() < MAX_NAME_LENGTH {
+			profile_name.0.push(event.char);
+		}
+	}
+	if keyboard.just_pressed(KeyCode::Back) {
+		profile_name.0.pop();
+	}
+	if keyboard.just_pressed(KeyCode::Return) && !profile_name.0.trim().is_empty() {
+		user_data.name = profile_name.0.trim().to_string();
+		next_game_state.set(GameState::Intro);
+	}
+	if profile_name.is_changed() {
+		for mut text in name_text_query.iter_mut() {
+			text.sections[0].value = format!("{}_", profile_name.0);
+		}
+	}
+}
+
+pub fn reset_menu_state(
+	mut next_menu_state: ResMut<NextState<MenuState>>,
+) {
+	next_menu_state.set(MenuState::Main);
+}
+
+pub fn quit_game_button_interaction(
+	mut button_query: ButtonInteraction<QuitGameButton>,
 	mut exit_app: EventWriter<AppExit>,
 ) {
 	if let Ok((interaction, mut background_color)) = button_query.get_single_mut() {
@@ -98,4 +316,4 @@ pub fn quit_game_button_interaction(
 			}
 		}
 	}
-}
\ No newline at end of file
+}
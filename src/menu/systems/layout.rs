@@ -1,6 +1,11 @@
 use bevy::prelude::*;
 use bevy::text::BreakLineOn;
+use bevy::ui::FocusPolicy;
+use bevy_pkv::PkvStore;
 
+use crate::save::{read_slot, SAVE_SLOTS, format::SaveError};
+
+use super::super::{MenuState, ProfileName, ImportInto};
 use super::super::styles::*;
 use super::super::components::*;
 
@@ -19,6 +24,185 @@ pub fn spawn_ui(
 	build_ui(&mut commands, &asset_server);
 }
 
+pub fn spawn_slot_browser(
+	mut commands: Commands,
+	asset_server: Res<AssetServer>,
+	menu_state: Res<State<MenuState>>,
+	pkv: Res<PkvStore>,
+) {
+	// Loading and exporting need a readable save in the slot
+	let loading = matches!(menu_state.0, MenuState::LoadGame | MenuState::ExportSave);
+	let title = match menu_state.0 {
+		MenuState::LoadGame => "Load Game",
+		MenuState::ExportSave => "Export Save",
+		MenuState::ImportSave => "Import Save",
+		_ => "New Game",
+	};
+	commands
+		// Slot Browser Node Bundle
+		.spawn(
+			(NodeBundle {
+				style: OVERLAY_STYLE,
+				background_color: OVERLAY_COLOR.into(),
+				z_index: ZIndex::Global(10),
+				focus_policy: FocusPolicy::Block,
+				..default()
+			},
+			SlotBrowser,
+		))
+		.with_children(|parent| {
+			// Slot Browser Title
+			parent.spawn(
+				TextBundle {
+					text: Text::from_section(title, get_button_text_style(&asset_server))
+						.with_alignment(TextAlignment::Center),
+					..default()
+			});
+			for slot in 0..SAVE_SLOTS {
+				let (description, color) = match read_slot(&pkv, slot) {
+					Ok(save_data) => (
+						format!("Slot {} - {} - Day {}, {:?}\nMoney {:.0}  Stress {:.0}  Flirt {:.0}  Royal {:.0}",
+							slot + 1, save_data.name, save_data.day, save_data.time,
+							save_data.money, save_data.stress, save_data.flirt, save_data.royal),
+						IDLE_BUTTON_COLOR,
+					),
+					Err(SaveError::NotFound) => (
+						format!("Slot {} - Empty", slot + 1),
+						if loading {DISABLED_BUTTON_COLOR} else {IDLE_BUTTON_COLOR},
+					),
+					Err(e) => {
+						error!("Slot {}: {}", slot + 1, e);
+						(
+							format!("Slot {} - {}", slot + 1, e),
+							if loading {DISABLED_BUTTON_COLOR} else {IDLE_BUTTON_COLOR},
+						)
+					}
+				};
+				// Slot Button
+				parent.spawn(
+					(
+						ButtonBundle {
+							style: SLOT_BUTTON_STYLE,
+							background_color: color.into(),
+							..default()
+						},
+						SlotButton(slot),
+					))
+					.with_children(|parent| {
+						// Slot Button Text
+						parent.spawn(
+							TextBundle {
+								text: Text::from_section(description, get_slot_text_style(&asset_server))
+									.with_alignment(TextAlignment::Center),
+								..default()
+						});
+					})
+				;
+			}
+			build_button(parent, &asset_server, "Back", BackButton);
+		});
+}
+
+pub fn spawn_confirm_overwrite(
+	mut commands: Commands,
+	asset_server: Res<AssetServer>,
+	import_into: Res<ImportInto>,
+) {
+	let warning = match import_into.0 {
+		Some(_) => "This slot already has a save.\nImporting will overwrite it!",
+		None => "This slot already has a save.\nStarting a new game will overwrite it!",
+	};
+	commands
+		// Confirm Overwrite Node Bundle
+		.spawn(
+			(NodeBundle {
+				style: OVERLAY_STYLE,
+				background_color: OVERLAY_COLOR.into(),
+				z_index: ZIndex::Global(10),
+				focus_policy: FocusPolicy::Block,
+				..default()
+			},
+			ConfirmOverwritePopup,
+		))
+		.with_children(|parent| {
+			// Warning Text
+			parent.spawn(
+				TextBundle {
+					text: Text::from_section(warning, get_button_text_style(&asset_server))
+						.with_alignment(TextAlignment::Center),
+					..default()
+			});
+			build_button(parent, &asset_server, "Overwrite", ConfirmOverwriteButton);
+			build_button(parent, &asset_server, "Back", BackButton);
+		});
+}
+
+pub fn spawn_name_entry(
+	mut commands: Commands,
+	asset_server: Res<AssetServer>,
+	mut profile_name: ResMut<ProfileName>,
+) {
+	profile_name.0.clear();
+	commands
+		// Name Entry Node Bundle
+		.spawn(
+			(NodeBundle {
+				style: OVERLAY_STYLE,
+				background_color: OVERLAY_COLOR.into(),
+				z_index: ZIndex::Global(10),
+				focus_policy: FocusPolicy::Block,
+				..default()
+			},
+			NameEntryPopup,
+		))
+		.with_children(|parent| {
+			// Name Prompt
+			parent.spawn(
+				TextBundle {
+					text: Text::from_section("What's your name?", get_button_text_style(&asset_server))
+						.with_alignment(TextAlignment::Center),
+					..default()
+			});
+			// Typed Name
+			parent.spawn((
+				TextBundle {
+					text: Text::from_section("_", get_button_text_style(&asset_server))
+						.with_alignment(TextAlignment::Center),
+					..default()
+				},
+				NameEntryText,
+			));
+			build_button(parent, &asset_server, "Start", StartGameButton);
+			build_button(parent, &asset_server, "Back", BackButton);
+		});
+}
+
+pub fn build_button(
+	parent: &mut ChildBuilder,
+	asset_server: &Res<AssetServer>,
+	label: &str,
+	marker: impl Component,
+) {
+	parent.spawn(
+		(
+			ButtonBundle {
+				style: BUTTON_STYLE,
+				background_color: IDLE_BUTTON_COLOR.into(),
+				..default()
+			},
+			marker,
+		))
+		.with_children(|parent| {
+			parent.spawn(
+				TextBundle {
+					text: Text::from_section(label, get_button_text_style(asset_server))
+						.with_alignment(TextAlignment::Center),
+					..default()
+			});
+		})
+	;
+}
+
 pub fn build_menu(
 	commands: &mut Commands,
 	asset_server: &Res<AssetServer>
@@ -114,6 +298,93 @@ pub fn build_menu(
 				})
 			;
 
+			// Export Save Button
+			parent.spawn(
+				(
+					ButtonBundle {
+						style: BUTTON_STYLE,
+						background_color: IDLE_BUTTON_COLOR.into(),
+						..default()
+					},
+					ExportSaveButton,
+				))
+				.with_children(|parent| {
+					// Export Save Button Text
+					parent.spawn(
+						TextBundle {
+							text: Text {
+								sections: vec![
+									TextSection::new(
+										"Export Save",
+										get_button_text_style(asset_server),
+									)
+								],
+								alignment: TextAlignment::Center,
+								..default()
+							},
+							..default()
+					});
+				})
+			;
+
+			// Import Save Button
+			parent.spawn(
+				(
+					ButtonBundle {
+						style: BUTTON_STYLE,
+						background_color: IDLE_BUTTON_COLOR.into(),
+						..default()
+					},
+					ImportSaveButton,
+				))
+				.with_children(|parent| {
+					// Import Save Button Text
+					parent.spawn(
+						TextBundle {
+							text: Text {
+								sections: vec![
+									TextSection::new(
+										"Import Save",
+										get_button_text_style(asset_server),
+									)
+								],
+								alignment: TextAlignment::Center,
+								..default()
+							},
+							..default()
+					});
+				})
+			;
+
+			// Options Button
+			parent.spawn(
+				(
+					ButtonBundle {
+						style: BUTTON_STYLE,
+						background_color: IDLE_BUTTON_COLOR.into(),
+						..default()
+					},
+					OptionsButton,
+				))
+				.with_children(|parent| {
+					// Options Button Text
+					parent.spawn(
+						TextBundle {
+							text: Text {
+								sections: vec![
+									TextSection::new(
+										"Options",
+										get_button_text_style(asset_server),
+									)
+								],
+								alignment: TextAlignment::Center,
+								..default()
+							},
+							..default()
+					});
+				})
+			;
+
 			// Quit Game Button
 			parent.spawn(
 				(
@@ -0,0 +1,176 @@
+// Mid-level autosaves
+// Every few seconds the live board is captured into UserData.level and written to the current slot,
+// each minigame fills in its own part of the snapshot and restores it again after loading
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use serde::{Serialize, Deserialize};
+
+use crate::{setup::UserData, game::{states::{DayState, GameState, PauseState, DayTimer}, food::DropType, minigames::orders::Order}};
+
+use super::SaveGame;
+
+pub const AUTOSAVE_INTERVAL: f32 = 10.0;
+
+pub struct AutosavePlugin;
+
+impl Plugin for AutosavePlugin {
+	fn build(&self, app: &mut App) {
+		app
+		.insert_resource(AutosaveTimer(Timer::from_seconds(AUTOSAVE_INTERVAL, TimerMode::Repeating)))
+		.add_event::<TakeSnapshot>()
+		.configure_sets((AutosaveSet::Begin, AutosaveSet::Capture, AutosaveSet::Write).chain())
+		.configure_set(AutosaveSet::Capture
+			.run_if(on_event::<TakeSnapshot>())
+			.run_if(in_state(GameState::Game))
+			.run_if(not(resource_exists::<RestoreLevel>())))
+		.configure_set(AutosaveSet::Write
+			.run_if(on_event::<TakeSnapshot>())
+			.run_if(in_state(GameState::Game))
+			.run_if(not(resource_exists::<RestoreLevel>())))
+		.configure_sets((AutosaveSet::Restore, AutosaveSet::FinishRestore).chain())
+		.configure_set(AutosaveSet::Restore.run_if(board_ready))
+		.configure_set(AutosaveSet::FinishRestore.run_if(board_ready))
+		.add_system(tick_autosave
+			.in_set(AutosaveSet::Begin)
+			.run_if(not(in_state(DayState::Dawn)))
+			.run_if(in_state(GameState::Game))
+			.run_if(in_state(PauseState::Unpaused))
+			.run_if(not(resource_exists::<RestoreLevel>())))
+		.add_system(begin_snapshot
+			.in_set(AutosaveSet::Begin)
+			.after(tick_autosave)
+			.run_if(on_event::<TakeSnapshot>())
+			.run_if(not(in_state(DayState::Dawn)))
+			.run_if(in_state(GameState::Game))
+			.run_if(not(resource_exists::<RestoreLevel>())))
+		.add_system(write_autosave.in_set(AutosaveSet::Write))
+		.add_system(restore_clock.in_set(AutosaveSet::Restore))
+		.add_system(finish_restore.in_set(AutosaveSet::FinishRestore))
+		;
+	}
+}
+
+#[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone, Copy)]
+pub enum AutosaveSet {
+	// Starts a fresh snapshot in UserData.level
+	Begin,
+	// Minigames copy their board into the snapshot
+	Capture,
+	Write,
+	// Minigames rebuild their board from RestoreLevel, after the level has been spawned
+	Restore,
+	FinishRestore,
+}
+
+#[derive(Resource)]
+pub struct AutosaveTimer(pub Timer);
+
+// Captures the board into UserData.level and saves it, sent every few seconds or whenever the game is left mid-level
+pub struct TakeSnapshot;
+
+// Present while a loaded level is waiting to be rebuilt from its snapshot
+#[derive(Resource)]
+pub struct RestoreLevel(pub LevelSnapshot);
+
+#[derive(Serialize, Deserialize, Default, Clone, PartialEq, Debug)]
+pub struct LevelSnapshot {
+	pub time: DayState,
+	// Seconds of DayTimer already used up
+	pub elapsed: f32,
+	// Evening, item pegs which haven't been eaten yet
+	pub food_pegs: Vec<SavedPeg>,
+	// Night, BoardIndex of every bell which has been rung
+	pub rung_bells: Vec<usize>,
+	// Morning, basket labels from left to right
+	pub basket_labels: Vec<DropType>,
+	pub hanging_balls: Vec<HangingBall>,
+	// Morning, customers still waiting and the combo built up so far
+	pub orders: Vec<Order>,
+	pub combo: u32,
+	// Morning, items parked in the storage bin
+	pub stored: Vec<DropType>,
+	// Evening, ingredients in the soup and the recipe card being cooked
+	pub soup: Vec<DropType>,
+	pub recipe: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct SavedPeg {
+	pub index: usize,
+	pub drop_type: DropType,
+	pub x: f32,
+	pub y: f32,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct HangingBall {
+	pub x: f32,
+	pub y: f32,
+	pub drop_type: DropType,
+	// Seconds of HangTimer already used up
+	pub elapsed: f32,
+}
+
+// Restoring waits until the level the snapshot was taken in has been spawned
+fn board_ready(
+	restore: Option<Res<RestoreLevel>>,
+	current_game_state: Res<State<GameState>>,
+	current_day_state: Res<State<DayState>>,
+	day_timer: Option<Res<DayTimer>>,
+) -> bool {
+	match restore {
+		Some(restore) => current_game_state.0 == GameState::Game
+			&& current_day_state.0 == restore.0.time
+			&& day_timer.is_some(),
+		None => false,
+	}
+}
+
+fn tick_autosave(
+	mut autosave_timer: ResMut<AutosaveTimer>,
+	time: Res<Time>,
+	mut snapshot_events: EventWriter<TakeSnapshot>,
+) {
+	autosave_timer.0.tick(time.delta());
+	if autosave_timer.0.just_finished() {
+		snapshot_events.send(TakeSnapshot);
+	}
+}
+
+fn begin_snapshot(
+	mut snapshot_events: EventReader<TakeSnapshot>,
+	day_timer: Res<DayTimer>,
+	current_day_state: Res<State<DayState>>,
+	mut user_data: ResMut<UserData>,
+) {
+	snapshot_events.clear();
+	// A finished level is saved by advance_time instead
+	user_data.level = (!day_timer.0.finished()).then(|| LevelSnapshot {
+		time: current_day_state.0,
+		elapsed: day_timer.0.elapsed_secs(),
+		..default()
+	});
+}
+
+fn write_autosave(
+	mut save_events: EventWriter<SaveGame>,
+) {
+	save_events.send(SaveGame);
+}
+
+fn restore_clock(
+	restore: Res<RestoreLevel>,
+	mut day_timer: ResMut<DayTimer>,
+) {
+	day_timer.0.set_elapsed(Duration::from_secs_f32(restore.0.elapsed));
+}
+
+fn finish_restore(
+	mut commands: Commands,
+	mut autosave_timer: ResMut<AutosaveTimer>,
+) {
+	commands.remove_resource::<RestoreLevel>();
+	autosave_timer.0.reset();
+}
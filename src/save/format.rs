@@ -0,0 +1,231 @@
+// Versioned save format
+// Every save is wrapped in an envelope which records the version it was written with,
+// older saves are brought up to date by running them through the migrations below
+
+use std::fmt;
+
+use bevy_pkv::PkvStore;
+use serde::{Serialize, Deserialize};
+use serde_json::{Value, json};
+
+use crate::{setup::UserData, game::states::DayState};
+
+// Bump this and add a migration whenever UserData changes shape
+pub const SAVE_VERSION: u32 = 6;
+
+// Migrations from version n to version n + 1, indexed by n
+const MIGRATIONS: [fn(&mut Value); SAVE_VERSION as usize] = [
+	migrate_v0_to_v1,
+	migrate_v1_to_v2,
+	migrate_v2_to_v3,
+	migrate_v3_to_v4,
+	migrate_v4_to_v5,
+	migrate_v5_to_v6,
+];
+
+#[derive(Serialize, Deserialize)]
+pub struct SaveEnvelope {
+	pub version: u32,
+	// UserData serialised as json, so older versions can be migrated without their structs
+	pub payload: String,
+}
+
+// Saves written before the envelope existed, treated as version 0
+#[derive(Serialize, Deserialize)]
+struct LegacySaveData {
+	name: String,
+	money: f32,
+	stress: f32,
+	flirt: f32,
+	royal: f32,
+	drugs_taken: f32,
+	day: f32,
+	time: DayState,
+	lvl_init: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SaveError {
+	NotFound,
+	TooNew(u32),
+	Unreadable(String),
+}
+
+impl fmt::Display for SaveError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			SaveError::NotFound => write!(f, "No save found"),
+			SaveError::TooNew(version) => write!(f, "Save is from a newer version of the game (v{}, this game reads up to v{})", version, SAVE_VERSION),
+			SaveError::Unreadable(reason) => write!(f, "Save could not be read ({})", reason),
+		}
+	}
+}
+
+pub fn encode(user_data: &UserData) -> SaveEnvelope {
+	SaveEnvelope {
+		version: SAVE_VERSION,
+		payload: serde_json::to_string(user_data).expect("Unable to serialise save"),
+	}
+}
+
+pub fn decode(envelope: SaveEnvelope) -> Result<UserData, SaveError> {
+	if envelope.version > SAVE_VERSION {
+		return Err(SaveError::TooNew(envelope.version));
+	}
+	let value = serde_json::from_str(&envelope.payload)
+		.map_err(|e| SaveError::Unreadable(e.to_string()))?;
+	migrate(value, envelope.version)
+}
+
+pub fn load(pkv: &PkvStore, key: &str) -> Result<UserData, SaveError> {
+	if let Ok(envelope) = pkv.get::<SaveEnvelope>(key) {
+		return decode(envelope);
+	}
+	match pkv.get::<LegacySaveData>(key) {
+		Ok(legacy) => from_legacy(legacy),
+		Err(bevy_pkv::GetError::NotFound) => Err(SaveError::NotFound),
+		Err(e) => Err(SaveError::Unreadable(e.to_string())),
+	}
+}
+
+fn from_legacy(legacy: LegacySaveData) -> Result<UserData, SaveError> {
+	let value = serde_json::to_value(legacy)
+		.map_err(|e| SaveError::Unreadable(e.to_string()))?;
+	migrate(value, 0)
+}
+
+// Exported saves are the envelope as readable json, so they work between native and web builds
+pub fn to_file(user_data: &UserData) -> String {
+	serde_json::to_string_pretty(&encode(user_data)).expect("Unable to serialise save")
+}
+
+pub fn from_file(contents: &str) -> Result<UserData, SaveError> {
+	let envelope = serde_json::from_str(contents)
+		.map_err(|e| SaveError::Unreadable(e.to_string()))?;
+	decode(envelope)
+}
+
+pub fn store(pkv: &mut PkvStore, key: &str, user_data: &UserData) {
+	pkv.set(key, &encode(user_data))
+		.expect("Unable to store user");
+}
+
+fn migrate(mut value: Value, version: u32) -> Result<UserData, SaveError> {
+	for migration in &MIGRATIONS[version as usize..] {
+		migration(&mut value);
+	}
+	serde_json::from_value(value).map_err(|e| SaveError::Unreadable(e.to_string()))
+}
+
+// v1 added unstable_royal, which starts settled
+fn migrate_v0_to_v1(value: &mut Value) {
+	if let Some(fields) = value.as_object_mut() {
+		fields.entry("unstable_royal").or_insert(json!(0.0));
+	}
+}
+
+// v2 saves UserData directly, lvl_init is runtime only and no longer stored
+fn migrate_v1_to_v2(value: &mut Value) {
+	if let Some(fields) = value.as_object_mut() {
+		fields.remove("lvl_init");
+	}
+}
+
+// v3 added mid-level autosaves, older saves were always written between levels
+fn migrate_v2_to_v3(value: &mut Value) {
+	if let Some(fields) = value.as_object_mut() {
+		fields.entry("level").or_insert(Value::Null);
+	}
+}
+
+// v4 added the work order queue to mid-level autosaves
+fn migrate_v3_to_v4(value: &mut Value) {
+	if let Some(level) = value.get_mut("level").and_then(Value::as_object_mut) {
+		level.entry("orders").or_insert(json!([]));
+		level.entry("combo").or_insert(json!(0));
+	}
+}
+
+// v5 added the storage bin to mid-level autosaves
+fn migrate_v4_to_v5(value: &mut Value) {
+	if let Some(level) = value.get_mut("level").and_then(Value::as_object_mut) {
+		level.entry("stored").or_insert(json!([]));
+	}
+}
+
+// v6 added the soup and its recipe card to mid-level autosaves
+fn migrate_v5_to_v6(value: &mut Value) {
+	if let Some(level) = value.get_mut("level").and_then(Value::as_object_mut) {
+		level.entry("soup").or_insert(json!([]));
+		level.entry("recipe").or_insert(json!(0));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{game::food::DropType, save::autosave::{LevelSnapshot, SavedPeg}};
+
+	use super::*;
+
+	#[test]
+	fn legacy_saves_are_migrated() {
+		let legacy = r#"{"name":"Old","money":12.0,"stress":30.0,"flirt":55.0,"royal":20.0,"drugs_taken":2.0,"day":3.0,"time":"Night","lvl_init":true}"#;
+		let user_data = from_legacy(serde_json::from_str(legacy).unwrap()).unwrap();
+		assert_eq!(user_data, UserData {
+			name: "Old".to_string(),
+			money: 12.0,
+			stress: 30.0,
+			flirt: 55.0,
+			royal: 20.0,
+			unstable_royal: 0.0,
+			drugs_taken: 2.0,
+			day: 3.0,
+			time: DayState::Night,
+			level: None,
+			lvl_init: false,
+		});
+	}
+
+	#[test]
+	fn v1_saves_drop_lvl_init() {
+		let payload = r#"{"name":"One","money":5.0,"stress":0.0,"flirt":50.0,"royal":0.0,"unstable_royal":0.5,"drugs_taken":0.0,"day":1.0,"time":"Morning","lvl_init":true}"#;
+		let user_data = decode(SaveEnvelope {version: 1, payload: payload.to_string()}).unwrap();
+		assert_eq!(user_data, UserData {
+			name: "One".to_string(),
+			money: 5.0,
+			flirt: 50.0,
+			unstable_royal: 0.5,
+			day: 1.0,
+			time: DayState::Morning,
+			..Default::default()
+		});
+	}
+
+	#[test]
+	fn v3_autosaves_gain_the_later_level_parts() {
+		let payload = r#"{"name":"Three","money":8.0,"stress":10.0,"flirt":50.0,"royal":5.0,"unstable_royal":0.0,"drugs_taken":1.0,"day":2.0,"time":"Evening",
+			"level":{"time":"Evening","elapsed":12.5,"food_pegs":[{"index":4,"drop_type":"Apple","x":152.0,"y":48.0}],"rung_bells":[],"basket_labels":[],"hanging_balls":[]}}"#;
+		let user_data = decode(SaveEnvelope {version: 3, payload: payload.to_string()}).unwrap();
+		assert_eq!(user_data.level, Some(LevelSnapshot {
+			time: DayState::Evening,
+			elapsed: 12.5,
+			food_pegs: vec![SavedPeg {index: 4, drop_type: DropType::Apple, x: 152.0, y: 48.0}],
+			..Default::default()
+		}));
+		assert_eq!(user_data.money, 8.0);
+	}
+
+	#[test]
+	fn newer_saves_are_refused() {
+		let envelope = encode(&UserData::default());
+		let result = decode(SaveEnvelope {version: SAVE_VERSION + 1, ..envelope});
+		assert_eq!(result, Err(SaveError::TooNew(SAVE_VERSION + 1)));
+	}
+
+	#[test]
+	fn garbage_is_unreadable() {
+		assert!(matches!(from_file("not a save"), Err(SaveError::Unreadable(_))));
+		let envelope = SaveEnvelope {version: SAVE_VERSION, payload: "{\"money\": \"lots\"}".to_string()};
+		assert!(matches!(decode(envelope), Err(SaveError::Unreadable(_))));
+	}
+}
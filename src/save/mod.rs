@@ -0,0 +1,150 @@
+use bevy::prelude::*;
+use bevy_pkv::PkvStore;
+
+use crate::{setup::UserData, game::states::{GameState, DayState, PauseState}};
+
+use self::{format::SaveError, autosave::{AutosavePlugin, RestoreLevel}, transfer::TransferPlugin};
+
+pub mod autosave;
+pub mod format;
+pub mod transfer;
+
+pub const SAVE_SLOTS: usize = 3;
+// Key used before save slots existed, only read to move old saves into the first slot
+const LEGACY_SAVE_KEY: &str = "user_info";
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+	fn build(&self, app: &mut App) {
+		app
+		.add_plugin(AutosavePlugin)
+		.add_plugin(TransferPlugin)
+		.insert_resource(CurrentSlot(0))
+		.add_event::<SaveGame>()
+		.add_event::<LoadGame>()
+		.add_startup_system(migrate_legacy_save)
+		.add_system(save_game.run_if(on_event::<SaveGame>()))
+		.add_system(load_game.run_if(on_event::<LoadGame>()))
+		;
+	}
+}
+
+// The slot that the current game is saved into
+#[derive(Resource)]
+pub struct CurrentSlot(pub usize);
+
+// Writes the current UserData into the current slot
+pub struct SaveGame;
+
+// Replaces UserData with the contents of a slot and drops the player back into the game,
+// mid-level saves also bring back the board as it was
+pub struct LoadGame(pub usize);
+
+pub fn slot_key(slot: usize) -> String {
+	format!("save_slot_{}", slot)
+}
+
+pub fn read_slot(pkv: &PkvStore, slot: usize) -> Result<UserData, SaveError> {
+	format::load(pkv, &slot_key(slot))
+}
+
+pub fn write_slot(pkv: &mut PkvStore, slot: usize, user_data: &UserData) {
+	format::store(pkv, &slot_key(slot), user_data);
+}
+
+// Unreadable saves still count, so the slot browser can explain what went wrong
+pub fn any_slot_used(pkv: &PkvStore) -> bool {
+	(0..SAVE_SLOTS).any(|slot| !matches!(read_slot(pkv, slot), Err(SaveError::NotFound)))
+}
+
+fn save_game(
+	mut events: EventReader<SaveGame>,
+	mut pkv: ResMut<PkvStore>,
+	current_slot: Res<CurrentSlot>,
+	user_data: Res<UserData>,
+) {
+	events.clear();
+	write_slot(&mut pkv, current_slot.0, &user_data);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn load_game(
+	mut commands: Commands,
+	mut events: EventReader<LoadGame>,
+	pkv: Res<PkvStore>,
+	mut current_slot: ResMut<CurrentSlot>,
+	mut user_data: ResMut<UserData>,
+	mut next_game_state: ResMut<NextState<GameState>>,
+	mut next_day_state: ResMut<NextState<DayState>>,
+	mut next_pause_state: ResMut<NextState<PauseState>>,
+) {
+	if let Some(LoadGame(slot)) = events.iter().last() {
+		match read_slot(&pkv, *slot) {
+			Ok(save_data) => {
+				current_slot.0 = *slot;
+				*user_data = save_data;
+				if user_data.time != DayState::Dawn {
+					next_day_state.set(user_data.time);
+				}
+				match &user_data.level {
+					Some(level) if level.time == user_data.time => commands.insert_resource(RestoreLevel(level.clone())),
+					_ => commands.remove_resource::<RestoreLevel>(),
+				}
+				next_game_state.set(GameState::Game);
+				next_pause_state.set(PauseState::Paused);
+			}
+			Err(e) => error!("Unable to load slot {}: {}", slot + 1, e),
+		}
+	}
+}
+
+fn migrate_legacy_save(
+	mut pkv: ResMut<PkvStore>,
+) {
+	if let Ok(save_data) = format::load(&pkv, LEGACY_SAVE_KEY) {
+		if matches!(read_slot(&pkv, 0), Err(SaveError::NotFound)) {
+			info!("Moving old save into slot 1");
+			write_slot(&mut pkv, 0, &save_data);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::game::{food::DropType, minigames::orders::Order};
+
+	use super::{*, autosave::{LevelSnapshot, SavedPeg, HangingBall}};
+
+	#[test]
+	fn save_then_load_leaves_user_data_unchanged() {
+		let user_data = UserData {
+			name: "Tester".to_string(),
+			money: 42.5,
+			stress: 12.0,
+			flirt: 77.0,
+			royal: 33.0,
+			unstable_royal: 0.75,
+			drugs_taken: 4.0,
+			day: 9.0,
+			time: DayState::Evening,
+			level: Some(LevelSnapshot {
+				time: DayState::Evening,
+				elapsed: 31.5,
+				food_pegs: vec![SavedPeg {index: 3, drop_type: DropType::Apple, x: 104.0, y: 96.0}],
+				rung_bells: vec![1, 4],
+				basket_labels: vec![DropType::Cd, DropType::Bagel],
+				hanging_balls: vec![HangingBall {x: 200.0, y: 300.0, drop_type: DropType::Bun, elapsed: 0.5}],
+				orders: vec![Order {items: vec![DropType::Cd], filled: vec![true], patience: 20.0, waited: 5.0, mistakes: 1}],
+				combo: 3,
+				stored: vec![DropType::Apple],
+				soup: vec![DropType::Onion, DropType::Salt],
+				recipe: 2,
+			}),
+			// Runtime only, never written
+			lvl_init: false,
+		};
+		let loaded = format::decode(format::encode(&user_data)).unwrap();
+		assert_eq!(loaded, user_data);
+	}
+}
@@ -0,0 +1,196 @@
+// Exporting and importing saves as portable files
+// Native builds go through the system file dialog, the web build downloads and uploads through the browser,
+// so progress can be moved between the two and backed up outside of bevy_pkv
+
+use std::sync::{Arc, Mutex};
+
+use bevy::prelude::*;
+use bevy_pkv::PkvStore;
+
+use super::{read_slot, write_slot, format};
+
+pub const SAVE_FILE_EXTENSION: &str = "json";
+
+pub struct TransferPlugin;
+
+impl Plugin for TransferPlugin {
+	fn build(&self, app: &mut App) {
+		app
+		.add_event::<ExportSave>()
+		.add_event::<ImportSave>()
+		.add_event::<SaveImported>()
+		.add_system(export_save.run_if(on_event::<ExportSave>()))
+		.add_system(import_save.run_if(on_event::<ImportSave>()))
+		.add_system(finish_import.run_if(resource_exists::<PendingImport>()))
+		;
+	}
+}
+
+// Writes the save in a slot out to a file of the player's choosing
+pub struct ExportSave(pub usize);
+
+// Asks the player for a save file and writes it into a slot
+pub struct ImportSave(pub usize);
+
+// Sent once an imported save has been written into its slot
+pub struct SaveImported;
+
+// File picked for an import, the browser only hands it over some time after the pick
+#[derive(Resource)]
+struct PendingImport {
+	slot: usize,
+	contents: Arc<Mutex<Option<String>>>,
+}
+
+fn export_save(
+	mut events: EventReader<ExportSave>,
+	pkv: Res<PkvStore>,
+) {
+	for ExportSave(slot) in events.iter() {
+		match read_slot(&pkv, *slot) {
+			Ok(save_data) => {
+				// Names are typed in freely, so keep anything a file system might object to out of the file name
+				let name: String = save_data.name.chars().filter(|c| c.is_alphanumeric()).collect();
+				let file_name = format!("a_high_stakes_affair_{}.{}", name, SAVE_FILE_EXTENSION);
+				if let Err(e) = platform::save_file(&file_name, &format::to_file(&save_data)) {
+					error!("Unable to export slot {}: {}", slot + 1, e);
+				}
+			}
+			Err(e) => error!("Unable to export slot {}: {}", slot + 1, e),
+		}
+	}
+}
+
+fn import_save(
+	mut commands: Commands,
+	mut events: EventReader<ImportSave>,
+) {
+	if let Some(ImportSave(slot)) = events.iter().last() {
+		let contents = Arc::new(Mutex::new(None));
+		match platform::pick_file(contents.clone()) {
+			Ok(()) => commands.insert_resource(PendingImport { slot: *slot, contents }),
+			Err(e) => error!("Unable to import into slot {}: {}", slot + 1, e),
+		}
+	}
+}
+
+fn finish_import(
+	mut commands: Commands,
+	pending_import: Res<PendingImport>,
+	mut pkv: ResMut<PkvStore>,
+	mut imported_events: EventWriter<SaveImported>,
+) {
+	let contents = pending_import.contents.lock().unwrap().take();
+	if let Some(contents) = contents {
+		match format::from_file(&contents) {
+			Ok(save_data) => {
+				write_slot(&mut pkv, pending_import.slot, &save_data);
+				imported_events.send(SaveImported);
+			}
+			Err(e) => error!("Unable to import into slot {}: {}", pending_import.slot + 1, e),
+		}
+		commands.remove_resource::<PendingImport>();
+	}
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod platform {
+	use std::{fs, sync::{Arc, Mutex}};
+
+	use bevy::tasks::IoTaskPool;
+	use rfd::AsyncFileDialog;
+
+	use super::SAVE_FILE_EXTENSION;
+
+	// The dialog waits on a task so the game keeps running while it is open
+	pub fn save_file(file_name: &str, contents: &str) -> Result<(), String> {
+		let dialog = AsyncFileDialog::new()
+			.set_file_name(file_name)
+			.add_filter("Save file", &[SAVE_FILE_EXTENSION]);
+		let contents = contents.to_string();
+		IoTaskPool::get().spawn(async move {
+			// No file means the dialog was cancelled
+			if let Some(file) = dialog.save_file().await {
+				if let Err(e) = fs::write(file.path(), contents) {
+					bevy::log::error!("Unable to write save file: {}", e);
+				}
+			}
+		}).detach();
+		Ok(())
+	}
+
+	// Handed over through contents like the browser does, once the player has picked a file
+	pub fn pick_file(contents: Arc<Mutex<Option<String>>>) -> Result<(), String> {
+		let dialog = AsyncFileDialog::new()
+			.add_filter("Save file", &[SAVE_FILE_EXTENSION]);
+		IoTaskPool::get().spawn(async move {
+			if let Some(file) = dialog.pick_file().await {
+				match fs::read_to_string(file.path()) {
+					Ok(text) => *contents.lock().unwrap() = Some(text),
+					Err(e) => bevy::log::error!("Unable to read save file: {}", e),
+				}
+			}
+		}).detach();
+		Ok(())
+	}
+}
+
+#[cfg(target_arch = "wasm32")]
+mod platform {
+	use std::sync::{Arc, Mutex};
+
+	use wasm_bindgen::prelude::*;
+	use wasm_bindgen_futures::{spawn_local, JsFuture};
+	use web_sys::{Blob, BlobPropertyBag, Document, HtmlAnchorElement, HtmlInputElement, Url};
+
+	use super::SAVE_FILE_EXTENSION;
+
+	fn document() -> Result<Document, String> {
+		web_sys::window()
+			.and_then(|window| window.document())
+			.ok_or_else(|| "No document to attach to".to_string())
+	}
+
+	fn js_error(e: JsValue) -> String {
+		format!("{:?}", e)
+	}
+
+	// Downloads the save through a temporary link
+	pub fn save_file(file_name: &str, contents: &str) -> Result<(), String> {
+		let parts = js_sys::Array::of1(&JsValue::from_str(contents));
+		let blob = Blob::new_with_str_sequence_and_options(&parts, BlobPropertyBag::new().type_("application/json"))
+			.map_err(js_error)?;
+		let url = Url::create_object_url_with_blob(&blob).map_err(js_error)?;
+		let link: HtmlAnchorElement = document()?
+			.create_element("a").map_err(js_error)?
+			.dyn_into().map_err(js_error)?;
+		link.set_href(&url);
+		link.set_download(file_name);
+		link.click();
+		Url::revoke_object_url(&url).map_err(js_error)
+	}
+
+	// Opens the browser's upload dialog, the file is read in once the player has picked one
+	pub fn pick_file(contents: Arc<Mutex<Option<String>>>) -> Result<(), String> {
+		let input: HtmlInputElement = document()?
+			.create_element("input").map_err(js_error)?
+			.dyn_into().map_err(js_error)?;
+		input.set_type("file");
+		input.set_accept(&format!(".{}", SAVE_FILE_EXTENSION));
+		let picker = input.clone();
+		let on_change = Closure::once(move || {
+			if let Some(file) = picker.files().and_then(|files| files.get(0)) {
+				spawn_local(async move {
+					match JsFuture::from(file.text()).await {
+						Ok(text) => *contents.lock().unwrap() = text.as_string(),
+						Err(e) => bevy::log::error!("Unable to read save file: {}", js_error(e)),
+					}
+				});
+			}
+		});
+		input.set_onchange(Some(on_change.as_ref().unchecked_ref()));
+		on_change.forget();
+		input.click();
+		Ok(())
+	}
+}
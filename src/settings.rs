@@ -0,0 +1,193 @@
+// Player settings, kept in the PkvStore under their own key so they survive restarts
+// and don't depend on any save slot
+
+use bevy::{prelude::*, window::{PrimaryWindow, WindowMode}};
+use bevy_pkv::PkvStore;
+use serde::{Serialize, Deserialize};
+
+use crate::audio::channels::AudioBus;
+
+const SETTINGS_KEY: &str = "settings";
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+	fn build(&self, app: &mut App) {
+		app
+		.init_resource::<Settings>()
+		// Before anything that plays audio or reads keys
+		.add_startup_system(load_settings.in_base_set(StartupSet::PreStartup))
+		.add_system(save_settings.run_if(resource_changed::<Settings>()))
+		.add_system(apply_window_settings.run_if(resource_changed::<Settings>()))
+		;
+	}
+}
+
+// Fields missing from older settings fall back to their defaults
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct Settings {
+	pub music_volume: f64,
+	pub sfx_volume: f64,
+	pub voice_volume: f64,
+	// Mutes everything, each bus can also be muted on its own
+	pub muted: bool,
+	pub music_muted: bool,
+	pub sfx_muted: bool,
+	pub voice_muted: bool,
+	// Scales every post processing effect, 0 turns them off
+	pub vfx_intensity: f32,
+	// Photosensitivity safe mode, keeps post processing slow and mild
+	pub safe_mode: bool,
+	pub fullscreen: bool,
+	pub key_bindings: KeyBindings,
+}
+
+impl Default for Settings {
+	fn default() -> Self {
+		Self {
+			music_volume: 1.0,
+			sfx_volume: 1.0,
+			voice_volume: 1.0,
+			muted: false,
+			music_muted: false,
+			sfx_muted: false,
+			voice_muted: false,
+			vfx_intensity: 1.0,
+			safe_mode: false,
+			fullscreen: false,
+			key_bindings: KeyBindings::default(),
+		}
+	}
+}
+
+impl Settings {
+	pub fn volume(&self, bus: AudioBus) -> f64 {
+		match bus {
+			AudioBus::Music => self.music_volume,
+			AudioBus::Sfx => self.sfx_volume,
+			AudioBus::Voice => self.voice_volume,
+		}
+	}
+
+	pub fn set_volume(&mut self, bus: AudioBus, volume: f64) {
+		match bus {
+			AudioBus::Music => self.music_volume = volume,
+			AudioBus::Sfx => self.sfx_volume = volume,
+			AudioBus::Voice => self.voice_volume = volume,
+		}
+	}
+
+	pub fn bus_muted(&self, bus: AudioBus) -> bool {
+		match bus {
+			AudioBus::Music => self.music_muted,
+			AudioBus::Sfx => self.sfx_muted,
+			AudioBus::Voice => self.voice_muted,
+		}
+	}
+
+	pub fn toggle_bus_mute(&mut self, bus: AudioBus) {
+		match bus {
+			AudioBus::Music => self.music_muted = !self.music_muted,
+			AudioBus::Sfx => self.sfx_muted = !self.sfx_muted,
+			AudioBus::Voice => self.voice_muted = !self.voice_muted,
+		}
+	}
+
+	// What the bus actually plays at once both mutes are taken into account
+	pub fn audible_volume(&self, bus: AudioBus) -> f64 {
+		if self.muted || self.bus_muted(bus) {0.0} else {self.volume(bus)}
+	}
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct KeyBindings {
+	pub pause: KeyCode,
+	pub mute: KeyCode,
+	pub mute_music: KeyCode,
+	pub mute_sfx: KeyCode,
+}
+
+impl Default for KeyBindings {
+	fn default() -> Self {
+		Self {
+			pause: KeyCode::P,
+			mute: KeyCode::M,
+			mute_music: KeyCode::N,
+			mute_sfx: KeyCode::B,
+		}
+	}
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum KeyAction {
+	Pause,
+	Mute,
+	MuteMusic,
+	MuteSfx,
+}
+
+impl KeyAction {
+	pub fn name(&self) -> &'static str {
+		match self {
+			KeyAction::Pause => "Pause",
+			KeyAction::Mute => "Mute",
+			KeyAction::MuteMusic => "Mute Music",
+			KeyAction::MuteSfx => "Mute Sound Effects",
+		}
+	}
+}
+
+impl KeyBindings {
+	pub fn get(&self, action: KeyAction) -> KeyCode {
+		match action {
+			KeyAction::Pause => self.pause,
+			KeyAction::Mute => self.mute,
+			KeyAction::MuteMusic => self.mute_music,
+			KeyAction::MuteSfx => self.mute_sfx,
+		}
+	}
+
+	pub fn set(&mut self, action: KeyAction, key: KeyCode) {
+		match action {
+			KeyAction::Pause => self.pause = key,
+			KeyAction::Mute => self.mute = key,
+			KeyAction::MuteMusic => self.mute_music = key,
+			KeyAction::MuteSfx => self.mute_sfx = key,
+		}
+	}
+}
+
+fn load_settings(
+	mut commands: Commands,
+	pkv: Res<PkvStore>,
+) {
+	match pkv.get::<Settings>(SETTINGS_KEY) {
+		Ok(settings) => commands.insert_resource(settings),
+		Err(bevy_pkv::GetError::NotFound) => (),
+		Err(e) => error!("Unable to read settings, using defaults: {}", e),
+	}
+}
+
+fn save_settings(
+	settings: Res<Settings>,
+	mut pkv: ResMut<PkvStore>,
+) {
+	if let Err(e) = pkv.set(SETTINGS_KEY, &*settings) {
+		error!("Unable to store settings: {}", e);
+	}
+}
+
+fn apply_window_settings(
+	settings: Res<Settings>,
+	mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+) {
+	if let Ok(mut window) = window_query.get_single_mut() {
+		// Sized fullscreen keeps the game's fixed resolution
+		let mode = if settings.fullscreen {WindowMode::SizedFullscreen} else {WindowMode::Windowed};
+		if window.mode != mode {
+			window.mode = mode;
+		}
+	}
+}
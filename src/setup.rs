@@ -3,6 +3,7 @@ use serde::{Serialize, Deserialize};
 
 use crate::vfx::RenderTargetImage;
 use crate::game::states::DayState;
+use crate::save::autosave::LevelSnapshot;
 
 pub fn spawn_camera(
 	mut commands: Commands,
@@ -46,7 +47,8 @@ pub fn despawn_entities_with<T: Component>(to_despawn: Query<Entity, With<T>>, m
 	}
 }
 
-#[derive(Resource)]
+// Game progress, this is exactly what gets written to and read from a save slot
+#[derive(Resource, Serialize, Deserialize, Default, Clone, PartialEq, Debug)]
 pub struct UserData {
 	pub name: String,
 	pub money: f32,
@@ -58,20 +60,23 @@ pub struct UserData {
 	pub drugs_taken: f32,
 	pub day: f32,
 	pub time: DayState,
+	// Board of the level in progress, None when saved between levels
+	pub level: Option<LevelSnapshot>,
+	// Whether the current level has been started, always false after loading
+	#[serde(skip)]
 	pub lvl_init: bool,
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct SaveData {
-	pub name: String,
-	pub money: f32,
-	pub stress: f32,
-	pub flirt: f32,
-	pub royal: f32,
-	pub drugs_taken: f32,
-	pub day: f32,
-	pub time: DayState,
-	pub lvl_init: bool,
+impl UserData {
+	pub fn new_game(name: String) -> Self {
+		Self {
+			name,
+			money: 30.0,
+			flirt: 50.0,
+			time: DayState::Night,
+			..default()
+		}
+	}
 }
 
 #[derive(Component)]
@@ -1,12 +1,16 @@
-use bevy::{prelude::{Handle, Image, Resource, Deref, DerefMut, SystemSet}, render::render_resource::ShaderType};
+use bevy::{prelude::{Handle, Image, Resource, Deref, DerefMut, SystemSet, ReflectResource}, reflect::{Reflect, FromReflect}};
 
 use self::post_processing::PostProcessingMaterial;
 
+pub use self::uniforms::*;
+
+pub mod particles;
 pub mod post_processing;
+mod uniforms;
 pub mod vfx_triggers;
 
 #[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
-pub struct VFXChangeSystemSet; // System set for all the systems which edit Res<PostProcessingSettings>
+pub struct VFXChangeSystemSet; // System set for all the systems which edit Res<PostProcessingSettings> or Res<PostProcessingChain>
 
 #[derive(Resource, Deref, DerefMut)]
 pub struct RenderTargetImage { // A handle to the image which should be rendered to in order to get post-processing
@@ -18,19 +22,11 @@ struct PostProcessingMaterialHandle {
 	pub handle: Handle<PostProcessingMaterial>
 }
 
-#[derive(ShaderType, Default, Resource, Clone, Copy)]
-pub struct PostProcessingSettings { // Each field is a multiple of 16 bytes
-	pub strength: PostProcessingStrength,
-	pub ripple: PostProcessingRipple,
-}
-
-#[derive(ShaderType, Clone, Copy)]
-pub struct PostProcessingStrength {
-	pub wave_distort: f32,
-	pub hue_shift: f32,
-	pub ripple_distort: f32,
-	pub contrast_distort: f32,
-}
+// Several ripples can spread at once, a new one takes the place of the one with the least left to show
+// keep RIPPLE_COUNT in sync with the shader
+pub const RIPPLE_COUNT: usize = 8;
+// Radius in UV past which the shader stops drawing a ripple
+const RIPPLE_REACH: f32 = 2.0;
 
 impl Default for PostProcessingStrength {
 	fn default() -> Self {
@@ -43,12 +39,50 @@ impl Default for PostProcessingStrength {
 	}
 }
 
-#[derive(ShaderType, Clone, Copy)]
-pub struct PostProcessingRipple {
-	pub start_time: f32,
-	pub start_x: f32,
-	pub start_y: f32,
-	pub velocity: f32,
+impl PostProcessingRipples {
+	// Starts a ripple at a UV position, strength 1 is a full ripple
+	// Takes the place of the weakest ripple, counting how far each has already spread,
+	// and is dropped when every ripple still showing is stronger
+	pub fn push(&mut self, time: f32, x: f32, y: f32, strength: f32) {
+		let velocity = self.velocity;
+		let weakest = self.ripples.iter_mut()
+			.map(|ripple| (ripple.weight(time, velocity), ripple))
+			.min_by(|(a, _), (b, _)| a.total_cmp(b))
+			.expect("RIPPLE_COUNT is more than zero");
+		if weakest.0 > strength {
+			return;
+		}
+		*weakest.1 = PostProcessingRipple {
+			start_time: time,
+			start_x: x,
+			start_y: y,
+			strength,
+		};
+	}
+
+	pub fn latest_start(&self) -> f32 {
+		self.ripples.iter().map(|ripple| ripple.start_time).fold(f32::MIN, f32::max)
+	}
+}
+
+impl Default for PostProcessingRipples {
+	fn default() -> Self {
+		Self {
+			ripples: [PostProcessingRipple::default(); RIPPLE_COUNT],
+			velocity: 0.2,
+			_wasm_padding_a: 0.0,
+			_wasm_padding_b: 0.0,
+			_wasm_padding_c: 0.0,
+		}
+	}
+}
+
+impl PostProcessingRipple {
+	// Strength scaled down by how much of its spread is used up, 0 once it's gone off screen
+	fn weight(&self, time: f32, velocity: f32) -> f32 {
+		let spread = velocity * (time - self.start_time) / RIPPLE_REACH;
+		self.strength * (1.0 - spread).clamp(0.0, 1.0)
+	}
 }
 
 impl Default for PostProcessingRipple {
@@ -57,7 +91,131 @@ impl Default for PostProcessingRipple {
 			start_time: -100.0,
 			start_x: 0.5,
 			start_y: 0.5,
-			velocity: 0.2,
+			strength: 0.0,
+		}
+	}
+}
+
+// Extra passes drawn after the distortion pass above, in order, each one reading what the one before drew
+// The number of passes is fixed once the game has started, but they can be reordered, tuned and toggled
+#[derive(Resource, Reflect, Clone, Debug)]
+#[reflect(Resource)]
+pub struct PostProcessingChain {
+	pub passes: Vec<EffectPass>,
+}
+
+impl PostProcessingChain {
+	pub fn get(&self, kind: EffectKind) -> Option<&EffectPass> {
+		self.passes.iter().find(|pass| pass.kind == kind)
+	}
+
+	pub fn get_mut(&mut self, kind: EffectKind) -> Option<&mut EffectPass> {
+		self.passes.iter_mut().find(|pass| pass.kind == kind)
+	}
+}
+
+impl Default for PostProcessingChain {
+	fn default() -> Self {
+		Self {
+			passes: vec![
+				EffectPass::new(EffectKind::Pixelate, 1.0, 4.0),
+				EffectPass::new(EffectKind::Blur, 1.0, 2.0),
+				EffectPass::new(EffectKind::ChromaticAberration, 1.0, 6.0),
+				EffectPass::new(EffectKind::Crt, 0.35, 360.0),
+				EffectPass::new(EffectKind::Vignette, 0.5, 0.4),
+			],
+		}
+	}
+}
+
+#[derive(Reflect, FromReflect, Clone, Copy, Debug)]
+pub struct EffectPass {
+	pub kind: EffectKind,
+	pub enabled: bool,
+	// How much of the effect shows, from 0 to 1, scaled by the player's effect intensity
+	pub amount: f32,
+	// Size of the effect, what it means depends on the kind
+	pub scale: f32,
+}
+
+impl EffectPass {
+	// Passes start out disabled, vfx_triggers turns them on
+	pub fn new(kind: EffectKind, amount: f32, scale: f32) -> Self {
+		Self {
+			kind,
+			enabled: false,
+			amount,
+			scale,
+		}
+	}
+}
+
+// The number given to the shader, kept in sync with effect_pass.wgsl
+#[derive(Reflect, FromReflect, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EffectKind {
+	// Scanlines for the 80s look, scale is the number of lines down the screen
+	Crt,
+	// Darkened corners, scale is how far from the centre the darkening starts
+	Vignette,
+	// Colours split towards the edges, scale is the split in pixels at the corners
+	ChromaticAberration,
+	// Scale is the blur radius in pixels
+	Blur,
+	// Scale is the size of the blocks in pixels
+	Pixelate,
+}
+
+impl EffectKind {
+	pub fn shader_id(&self) -> u32 {
+		match self {
+			EffectKind::Crt => 0,
+			EffectKind::Vignette => 1,
+			EffectKind::ChromaticAberration => 2,
+			EffectKind::Blur => 3,
+			EffectKind::Pixelate => 4,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn strengths(ripples: &PostProcessingRipples) -> Vec<f32> {
+		ripples.ripples.iter().map(|ripple| ripple.strength).collect()
+	}
+
+	#[test]
+	fn weak_ripples_make_way_for_each_other_not_strong_ones() {
+		let mut ripples = PostProcessingRipples::default();
+		ripples.push(0.0, 0.5, 0.5, 1.0);
+		for i in 0..RIPPLE_COUNT * 3 {
+			ripples.push(0.1 * i as f32, 0.2, 0.2, 0.3);
+		}
+		assert_eq!(strengths(&ripples).iter().filter(|strength| **strength == 1.0).count(), 1);
+		assert_eq!(ripples.latest_start(), 0.1 * (RIPPLE_COUNT * 3 - 1) as f32);
+	}
+
+	#[test]
+	fn weak_ripples_are_dropped_while_strong_ones_show() {
+		let mut ripples = PostProcessingRipples::default();
+		for _ in 0..RIPPLE_COUNT {
+			ripples.push(0.0, 0.5, 0.5, 1.0);
+		}
+		ripples.push(1.0, 0.2, 0.2, 0.3);
+		assert_eq!(strengths(&ripples), vec![1.0; RIPPLE_COUNT]);
+	}
+
+	#[test]
+	fn spent_ripples_are_replaced_first() {
+		let mut ripples = PostProcessingRipples::default();
+		for i in 0..RIPPLE_COUNT {
+			ripples.push(i as f32, 0.5, 0.5, 1.0);
 		}
+		// The first one has spread its full reach by now, the rest still show
+		let spent = RIPPLE_REACH / ripples.velocity;
+		ripples.push(spent, 0.2, 0.2, 0.3);
+		assert_eq!(ripples.ripples[0].strength, 0.3);
+		assert_eq!(ripples.ripples[0].start_time, spent);
 	}
 }
@@ -0,0 +1,278 @@
+// Small sprite particles flung out of hits, eats, bells and prizes
+// What each burst looks like is set in particles.ron, the game only says which burst and where
+
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use rand::Rng;
+use ron::error::{Position, SpannedError};
+use serde::Deserialize;
+
+use crate::{
+	game::{
+		physics::{BallCollisionEvent, BallCollisionEventType, BallTargetHit},
+		minigames::fever::AttackerGate,
+		states::{GameState, PauseState},
+		targets::ClearOnDayTransition,
+	},
+	setup::UserData,
+};
+
+// Above the balls, below the frame
+const PARTICLE_DEPTH: f32 = -0.8;
+// Peg hits slower than this don't spark
+const HIT_SPARK_SPEED: f32 = 300.0;
+// Bursts stop spawning past this many particles, so a busy board stays cheap
+const MAX_PARTICLES: usize = 800;
+// How many extra bursts' worth of particles full royal adds
+const ROYAL_DENSITY: f32 = 2.0;
+
+pub struct ParticlesPlugin;
+
+impl Plugin for ParticlesPlugin {
+	fn build(&self, app: &mut App) {
+		app
+		.add_event::<ParticleBurst>()
+		.init_resource::<ParticleEmitters>()
+		.add_system(hit_sparks.run_if(on_event::<BallCollisionEvent>()))
+		.add_system(target_bursts.run_if(on_event::<BallTargetHit>()))
+		.add_system(spawn_particles
+			.after(hit_sparks)
+			.after(target_bursts)
+			.run_if(on_event::<ParticleBurst>()))
+		.add_system(update_particles
+			.run_if(in_state(GameState::Game))
+			.run_if(in_state(PauseState::Unpaused)))
+		;
+	}
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Deserialize)]
+pub enum Emitter {
+	Hit,
+	Target,
+	Eat,
+	Bell,
+	Prize,
+}
+
+// A burst of the emitter's particles at a point on the board
+pub struct ParticleBurst {
+	pub emitter: Emitter,
+	pub pos: Vec2,
+}
+
+fn default_direction() -> f32 {
+	90.0
+}
+
+fn default_spread() -> f32 {
+	360.0
+}
+
+fn default_size() -> (f32, f32) {
+	(4.0, 4.0)
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct EmitterInfo {
+	pub count: u32,
+	pub lifetime: (f32, f32),
+	pub speed: (f32, f32),
+	#[serde(default = "default_direction")]
+	pub direction: f32,
+	#[serde(default = "default_spread")]
+	pub spread: f32,
+	#[serde(default)]
+	pub gravity: f32,
+	#[serde(default = "default_size")]
+	pub size: (f32, f32),
+	pub colours: Vec<(f32, f32, f32, f32)>,
+}
+
+impl EmitterInfo {
+	// Colour at a point of the particle's life, from 0 to 1
+	pub fn colour(&self, life: f32) -> Color {
+		let Some(&last) = self.colours.last() else {
+			return Color::WHITE;
+		};
+		let place = life.clamp(0.0, 1.0) * (self.colours.len() - 1) as f32;
+		let index = place.floor() as usize;
+		let (r, g, b, a) = match self.colours.get(index + 1) {
+			Some(&next) => {
+				let from = self.colours[index];
+				let t = place - index as f32;
+				(
+					from.0 + (next.0 - from.0) * t,
+					from.1 + (next.1 - from.1) * t,
+					from.2 + (next.2 - from.2) * t,
+					from.3 + (next.3 - from.3) * t,
+				)
+			},
+			None => last,
+		};
+		Color::rgba(r, g, b, a)
+	}
+}
+
+#[derive(Resource, Debug)]
+pub struct ParticleEmitters(pub BTreeMap<Emitter, EmitterInfo>);
+
+impl ParticleEmitters {
+	// Ranges are checked here, rand panics on a minimum above its maximum
+	pub fn parse(source: &str) -> Result<Self, SpannedError> {
+		let emitters: BTreeMap<Emitter, EmitterInfo> = ron::from_str(source)?;
+		for (emitter, info) in emitters.iter() {
+			for (name, (min, max)) in [("lifetime", info.lifetime), ("speed", info.speed), ("size", info.size)] {
+				if min > max {
+					// Points at the emitter's entry, the numbers themselves aren't tracked past deserializing
+					let line = source.lines()
+						.position(|line| line.trim_start().starts_with(&format!("{:?}:", emitter)))
+						.map_or(0, |index| index + 1);
+					return Err(SpannedError {
+						code: ron::Error::Message(format!("{:?} {} goes from {} down to {}", emitter, name, min, max)),
+						position: Position {line, col: 1},
+					});
+				}
+			}
+		}
+		Ok(ParticleEmitters(emitters))
+	}
+}
+
+// Baked in like droppables.ron
+impl Default for ParticleEmitters {
+	fn default() -> Self {
+		ParticleEmitters::parse(include_str!("../../assets/data/particles.ron"))
+			.expect("assets/data/particles.ron is malformed")
+	}
+}
+
+#[derive(Component)]
+pub struct Particle {
+	emitter: Emitter,
+	velocity: Vec2,
+	gravity: f32,
+	age: f32,
+	lifetime: f32,
+}
+
+fn hit_sparks(
+	mut collision_events: EventReader<BallCollisionEvent>,
+	mut bursts: EventWriter<ParticleBurst>,
+) {
+	for collision in collision_events.iter() {
+		if let BallCollisionEventType::Peg {..} = collision.collision_type {
+			if collision.speed >= HIT_SPARK_SPEED {
+				bursts.send(ParticleBurst {emitter: Emitter::Hit, pos: collision.pos});
+			}
+		}
+	}
+}
+
+fn target_bursts(
+	mut target_events: EventReader<BallTargetHit>,
+	target_query: Query<(&Transform, Option<&AttackerGate>)>,
+	mut bursts: EventWriter<ParticleBurst>,
+) {
+	for hit in target_events.iter() {
+		if let Ok((transform, gate)) = target_query.get(hit.target) {
+			// Catching a ball in the fever gate wins a prize
+			let emitter = if gate.is_some() {Emitter::Prize} else {Emitter::Target};
+			bursts.send(ParticleBurst {emitter, pos: transform.translation.truncate()});
+		}
+	}
+}
+
+fn spawn_particles(
+	mut commands: Commands,
+	mut bursts: EventReader<ParticleBurst>,
+	emitters: Res<ParticleEmitters>,
+	particle_query: Query<(), With<Particle>>,
+	user_data: Res<UserData>,
+) {
+	let mut alive = particle_query.iter().count();
+	// The higher you are, the more there is to look at
+	let density = 1.0 + (user_data.royal / 100.0 + user_data.unstable_royal).clamp(0.0, 1.0) * ROYAL_DENSITY;
+	let mut rng = rand::thread_rng();
+	for burst in bursts.iter() {
+		let Some(info) = emitters.0.get(&burst.emitter) else {
+			warn!("{:?} has no particles in particles.ron", burst.emitter);
+			continue;
+		};
+		let count = ((info.count as f32 * density).round() as usize).min(MAX_PARTICLES.saturating_sub(alive));
+		alive += count;
+		for _ in 0..count {
+			let angle = (info.direction + info.spread * rng.gen_range(-0.5..=0.5)).to_radians();
+			let speed = rng.gen_range(info.speed.0..=info.speed.1);
+			let size = rng.gen_range(info.size.0..=info.size.1);
+			commands.spawn(
+				(
+					SpriteBundle {
+						transform: Transform::from_xyz(burst.pos.x, burst.pos.y, PARTICLE_DEPTH),
+						sprite: Sprite {
+							color: info.colour(0.0),
+							custom_size: Some(Vec2::splat(size)),
+							..default()
+						},
+						..default()
+					},
+					Particle {
+						emitter: burst.emitter,
+						velocity: Vec2::from_angle(angle) * speed,
+						gravity: info.gravity,
+						age: 0.0,
+						lifetime: rng.gen_range(info.lifetime.0..=info.lifetime.1),
+					},
+					ClearOnDayTransition,
+				)
+			);
+		}
+	}
+}
+
+fn update_particles(
+	mut commands: Commands,
+	mut particle_query: Query<(Entity, &mut Particle, &mut Transform, &mut Sprite)>,
+	emitters: Res<ParticleEmitters>,
+	time: Res<Time>,
+) {
+	let delta = time.delta_seconds();
+	for (entity, mut particle, mut transform, mut sprite) in particle_query.iter_mut() {
+		particle.age += delta;
+		if particle.age >= particle.lifetime {
+			commands.entity(entity).despawn();
+			continue;
+		}
+		particle.velocity.y -= particle.gravity * delta;
+		transform.translation += (particle.velocity * delta).extend(0.0);
+		if let Some(info) = emitters.0.get(&particle.emitter) {
+			sprite.color = info.colour(particle.age / particle.lifetime);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn every_emitter_has_particles() {
+		let emitters = ParticleEmitters::default();
+		for emitter in [Emitter::Hit, Emitter::Target, Emitter::Eat, Emitter::Bell, Emitter::Prize] {
+			let info = emitters.0.get(&emitter).unwrap_or_else(|| panic!("{:?} is missing from particles.ron", emitter));
+			assert!(!info.colours.is_empty(), "{:?} has no colours", emitter);
+			assert!(info.lifetime.0 > 0.0 && info.lifetime.0 <= info.lifetime.1, "{:?} has a bad lifetime", emitter);
+			assert!(info.speed.0 >= 0.0 && info.speed.0 <= info.speed.1, "{:?} has a bad speed", emitter);
+			assert!(info.size.0 > 0.0 && info.size.0 <= info.size.1, "{:?} has a bad size", emitter);
+		}
+	}
+
+	#[test]
+	fn backwards_ranges_are_refused() {
+		let source = "{\n\tHit: (count: 1, lifetime: (0.1, 0.2), speed: (200.0, 80.0), colours: []),\n}";
+		let error = ParticleEmitters::parse(source).unwrap_err();
+		assert_eq!(error.position.line, 2);
+		assert!(error.code.to_string().contains("speed"), "{}", error);
+	}
+}
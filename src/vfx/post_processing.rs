@@ -7,19 +7,31 @@ use bevy::{
 	prelude::*,
 	reflect::TypeUuid,
 	render::{
+		camera::RenderTarget,
 		render_resource::{
 			AsBindGroup, Extent3d, ShaderRef, TextureDescriptor, TextureDimension, TextureFormat,
-			TextureUsages, ShaderType,
+			TextureUsages,
 		},
 		texture::BevyDefault,
 		view::RenderLayers,
 	},
 	sprite::{Material2d, Material2dPlugin, MaterialMesh2dBundle},
+	window::WindowRef,
 };
 
-use crate::game::states::GameState;
+use crate::{game::states::GameState, settings::Settings};
 
-use super::{PostProcessingSettings, PostProcessingMaterialHandle};
+use super::{PostProcessingSettings, PostProcessingMaterialHandle, PostProcessingStrength, PostProcessingSafety,
+	PostProcessingChain, EffectPass, EffectKind, PostProcessingMaterialTime, EffectPassSettings};
+
+// Photosensitivity safe mode limits
+// Most any effect strength may change by per second
+const SAFE_CHANGE_RATE: f32 = 0.1;
+const SAFE_WAVE_LIMIT: f32 = 0.3;
+const SAFE_HUE_LIMIT: f32 = 1.0;
+const SAFE_CONTRAST_LIMIT: f32 = 0.15;
+const SAFE_VIGNETTE: f32 = 0.6;
+const SAFE_DESATURATE: f32 = 0.5;
 
 #[derive(Debug, Default)]
 pub struct VFXPlugin {}
@@ -28,18 +40,40 @@ impl Plugin for VFXPlugin {
 	fn build(&self, app: &mut App) {
 		app
 		.init_resource::<PostProcessingSettings>()
+		.init_resource::<PostProcessingChain>()
+		.register_type::<PostProcessingChain>()
+		.register_type::<EffectPass>()
+		.register_type::<EffectKind>()
 		.add_startup_system(vfx_setup.in_set(super::VFXChangeSystemSet))
 		.add_system(update_settings.after(super::VFXChangeSystemSet))
-		.add_plugin(Material2dPlugin::<PostProcessingMaterial>::default());
+		.add_system(update_effect_chain.after(super::VFXChangeSystemSet))
+		.add_plugin(Material2dPlugin::<PostProcessingMaterial>::default())
+		.add_plugin(Material2dPlugin::<EffectPassMaterial>::default());
 	}
 }
 
+// Cameras and materials of the effect passes, slot i draws pass i of the PostProcessingChain
+#[derive(Resource)]
+struct EffectChainSlots {
+	distort_camera: Entity,
+	slots: Vec<EffectSlot>,
+}
+
+struct EffectSlot {
+	camera: Entity,
+	material: Handle<EffectPassMaterial>,
+	// What the pass before this one draws into
+	input: Handle<Image>,
+}
+
 pub(crate) fn vfx_setup(
 	mut commands: Commands,
 	windows: Query<&Window>,
 	mut meshes: ResMut<Assets<Mesh>>,
 	mut post_processing_materials: ResMut<Assets<PostProcessingMaterial>>,
+	mut effect_materials: ResMut<Assets<EffectPassMaterial>>,
 	mut images: ResMut<Assets<Image>>,
+	chain: Res<PostProcessingChain>,
 ) {
 	// This assumes we only have a single window
 	let window = windows.single();
@@ -51,26 +85,7 @@ pub(crate) fn vfx_setup(
 	};
 
 	// This is the texture that will be rendered to.
-	let mut image = Image {
-		texture_descriptor: TextureDescriptor {
-			label: None,
-			size,
-			dimension: TextureDimension::D2,
-			format: TextureFormat::bevy_default(),
-			mip_level_count: 1,
-			sample_count: 1,
-			usage: TextureUsages::TEXTURE_BINDING
-				| TextureUsages::COPY_DST
-				| TextureUsages::RENDER_ATTACHMENT,
-			view_formats: &[],
-		},
-		..default()
-	};
-
-	// fill image.data with zeroes
-	image.resize(size);
-
-	let image_handle = images.add(image);
+	let image_handle = images.add(render_target_image(size));
 
 	commands.insert_resource(super::RenderTargetImage {image: image_handle.clone()});
 
@@ -90,7 +105,7 @@ pub(crate) fn vfx_setup(
 	// Post processing 2d quad, with material using the render texture done by the main camera, with a custom shader.
 	commands.spawn((
 		MaterialMesh2dBundle {
-			mesh: quad_handle.into(),
+			mesh: quad_handle.clone().into(),
 			material: material_handle,
 			transform: Transform {
 				translation: Vec3::new(0.0, 0.0, 1.5),
@@ -102,7 +117,7 @@ pub(crate) fn vfx_setup(
 	));
 
 	// The post-processing pass camera.
-	commands.spawn((
+	let distort_camera = commands.spawn((
 		Camera2dBundle {
 			camera: Camera {
 				// renders after the first main camera which has default value: 0.
@@ -113,21 +128,155 @@ pub(crate) fn vfx_setup(
 		},
 		UiCameraConfig { show_ui: false },
 		post_processing_pass_layer,
-	));
+	)).id();
+
+	// Every effect pass gets the same setup on a layer of its own, below the distortion pass layer
+	let mut slots = Vec::new();
+	for (i, pass) in chain.passes.iter().enumerate() {
+		let layer = RenderLayers::layer((RenderLayers::TOTAL_LAYERS - 2 - i) as u8);
+		let input = images.add(render_target_image(size));
+		let material = effect_materials.add(EffectPassMaterial::new(input.clone(), pass));
+		commands.spawn((
+			MaterialMesh2dBundle {
+				mesh: quad_handle.clone().into(),
+				material: material.clone(),
+				transform: Transform {
+					translation: Vec3::new(0.0, 0.0, 1.5),
+					..default()
+				},
+				..default()
+			},
+			layer,
+		));
+		let camera = commands.spawn((
+			Camera2dBundle {
+				camera: Camera {
+					order: 2 + i as isize,
+					// Wired up by update_effect_chain
+					is_active: false,
+					..default()
+				},
+				..Camera2dBundle::default()
+			},
+			UiCameraConfig { show_ui: false },
+			layer,
+		)).id();
+		slots.push(EffectSlot {camera, material, input});
+	}
+	commands.insert_resource(EffectChainSlots {distort_camera, slots});
+}
+
+fn render_target_image(
+	size: Extent3d,
+) -> Image {
+	let mut image = Image {
+		texture_descriptor: TextureDescriptor {
+			label: None,
+			size,
+			dimension: TextureDimension::D2,
+			format: TextureFormat::bevy_default(),
+			mip_level_count: 1,
+			sample_count: 1,
+			usage: TextureUsages::TEXTURE_BINDING
+				| TextureUsages::COPY_DST
+				| TextureUsages::RENDER_ATTACHMENT,
+			view_formats: &[],
+		},
+		..default()
+	};
+
+	// fill image.data with zeroes
+	image.resize(size);
+	image
 }
 
 fn update_settings(
 	mut post_processing_materials: ResMut<Assets<PostProcessingMaterial>>,
 	handle: Res<PostProcessingMaterialHandle>,
 	settings: Res<PostProcessingSettings>,
-	time: Res<Time>
+	player_settings: Res<Settings>,
+	time: Res<Time>,
+	mut safe_strength: Local<PostProcessingStrength>,
+	mut safety_target: Local<PostProcessingSafety>,
 ) {
 	if let Some(material) = post_processing_materials.get_mut(&handle) {
 		material.settings = settings.clone();
+		// Player chosen effects intensity
+		let intensity = player_settings.vfx_intensity;
+		let strength = &mut material.settings.strength;
+		strength.wave_distort *= intensity;
+		strength.hue_shift *= intensity;
+		strength.contrast_distort *= intensity;
+		// A zero width ripple divides by zero in the shader
+		strength.ripple_distort = (strength.ripple_distort * intensity).max(0.0001);
+		if player_settings.safe_mode {
+			let wanted = *strength;
+			// Effects may only drift, never jump, and stay well below their usual peaks
+			let max_change = SAFE_CHANGE_RATE * time.delta_seconds();
+			safe_strength.wave_distort = approach(safe_strength.wave_distort, wanted.wave_distort.min(SAFE_WAVE_LIMIT), max_change);
+			safe_strength.hue_shift = approach(safe_strength.hue_shift, wanted.hue_shift.min(SAFE_HUE_LIMIT), max_change);
+			safe_strength.contrast_distort = approach(safe_strength.contrast_distort,
+				wanted.contrast_distort.clamp(-SAFE_CONTRAST_LIMIT, SAFE_CONTRAST_LIMIT), max_change);
+			strength.wave_distort = safe_strength.wave_distort;
+			strength.hue_shift = safe_strength.hue_shift;
+			strength.contrast_distort = safe_strength.contrast_distort;
+			// Highs show as a vignette and stress as washed out colours instead
+			let safety = &mut material.settings.safety;
+			safety.vignette = approach(safety_target.vignette, (wanted.hue_shift / 5.0).clamp(0.0, 1.0) * SAFE_VIGNETTE, max_change);
+			safety.desaturate = approach(safety_target.desaturate, (wanted.contrast_distort.abs() * 2.0).min(1.0) * SAFE_DESATURATE, max_change);
+			*safety_target = *safety;
+		} else {
+			*safe_strength = *strength;
+			*safety_target = PostProcessingSafety::default();
+		}
 		material.time.time = time.elapsed_seconds();
 	}
 }
 
+// Each enabled pass draws into the input of the next enabled one, the last draws to the window
+fn update_effect_chain(
+	mut effect_materials: ResMut<Assets<EffectPassMaterial>>,
+	mut cameras: Query<&mut Camera>,
+	slots: Res<EffectChainSlots>,
+	chain: Res<PostProcessingChain>,
+	player_settings: Res<Settings>,
+	time: Res<Time>,
+	mut wired: Local<Vec<bool>>,
+) {
+	let enabled: Vec<bool> = (0..slots.slots.len())
+		.map(|i| chain.passes.get(i).is_some_and(|pass| pass.enabled))
+		.collect();
+	if *wired != enabled {
+		let mut target = RenderTarget::Window(WindowRef::Primary);
+		for (slot, &enabled) in slots.slots.iter().zip(enabled.iter()).rev() {
+			if let Ok(mut camera) = cameras.get_mut(slot.camera) {
+				camera.is_active = enabled;
+				if enabled {
+					camera.target = target;
+					target = RenderTarget::Image(slot.input.clone());
+				}
+			}
+		}
+		if let Ok(mut camera) = cameras.get_mut(slots.distort_camera) {
+			camera.target = target;
+		}
+		*wired = enabled;
+	}
+
+	for (slot, pass) in slots.slots.iter().zip(chain.passes.iter()) {
+		if !pass.enabled {
+			continue;
+		}
+		if let Some(material) = effect_materials.get_mut(&slot.material) {
+			material.settings = EffectPassSettings::new(pass, player_settings.vfx_intensity, time.elapsed_seconds());
+		}
+	}
+}
+
+fn approach(current: f32, target: f32, max_change: f32) -> f32 {
+	current + (target - current).clamp(-max_change, max_change)
+}
+
 // Region below declares of the custom material handling post processing effect
 
 /// Our custom post processing material
@@ -144,14 +293,6 @@ pub struct PostProcessingMaterial {
 	time: PostProcessingMaterialTime,
 }
 
-#[derive(ShaderType, Default, Clone, Copy)]
-struct PostProcessingMaterialTime {
-	time: f32,
-	_wasm_padding_a: f32,
-	_wasm_padding_b: f32,
-	_wasm_padding_c: f32, // Padded to 16 bytes to make WASM happy
-}
-
 impl PostProcessingMaterial {
 	pub fn new(source_image: Handle<Image>) -> PostProcessingMaterial {
 		PostProcessingMaterial { 
@@ -167,3 +308,40 @@ impl Material2d for PostProcessingMaterial {
 		"shaders/custom_material_post_process.wgsl".into()
 	}
 }
+
+// A single effect of the PostProcessingChain, which one is up to the kind in its settings
+#[derive(AsBindGroup, TypeUuid, Clone)]
+#[uuid = "5f0c1a6e-2b8d-4c7e-9a13-d6e4b2f8c901"]
+pub struct EffectPassMaterial {
+	#[texture(0)]
+	#[sampler(1)]
+	source_image: Handle<Image>,
+	#[uniform(2)]
+	settings: EffectPassSettings,
+}
+
+impl EffectPassSettings {
+	fn new(pass: &EffectPass, intensity: f32, time: f32) -> Self {
+		Self {
+			kind: pass.kind.shader_id(),
+			amount: (pass.amount * intensity).clamp(0.0, 1.0),
+			scale: pass.scale,
+			time,
+		}
+	}
+}
+
+impl EffectPassMaterial {
+	pub fn new(source_image: Handle<Image>, pass: &EffectPass) -> EffectPassMaterial {
+		EffectPassMaterial {
+			source_image,
+			settings: EffectPassSettings::new(pass, 1.0, 0.0),
+		}
+	}
+}
+
+impl Material2d for EffectPassMaterial {
+	fn fragment_shader() -> ShaderRef {
+		"shaders/effect_pass.wgsl".into()
+	}
+}
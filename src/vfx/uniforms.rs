@@ -0,0 +1,66 @@
+// Uniforms handed to the post-processing shaders, their layouts have to match the WGSL structs
+// ShaderType derives a never called check function next to every field, outside the struct where an allow can't reach,
+// so this module holds the struct definitions and nothing else, their impls live with the code using them
+#![allow(dead_code)]
+
+use bevy::{prelude::Resource, render::render_resource::ShaderType};
+
+use super::RIPPLE_COUNT;
+
+#[derive(ShaderType, Default, Resource, Clone, Copy)]
+pub struct PostProcessingSettings { // Each field is a multiple of 16 bytes
+	pub strength: PostProcessingStrength,
+	pub ripples: PostProcessingRipples,
+	pub safety: PostProcessingSafety,
+}
+
+#[derive(ShaderType, Clone, Copy)]
+pub struct PostProcessingStrength {
+	pub wave_distort: f32,
+	pub hue_shift: f32,
+	pub ripple_distort: f32,
+	pub contrast_distort: f32,
+}
+
+#[derive(ShaderType, Clone, Copy)]
+pub struct PostProcessingRipples {
+	pub ripples: [PostProcessingRipple; RIPPLE_COUNT],
+	// How fast every ripple spreads, in UV per second
+	pub velocity: f32,
+	pub _wasm_padding_a: f32,
+	pub _wasm_padding_b: f32,
+	pub _wasm_padding_c: f32, // Padded to 16 bytes to make WASM happy
+}
+
+#[derive(ShaderType, Clone, Copy)]
+pub struct PostProcessingRipple {
+	pub start_time: f32,
+	pub start_x: f32,
+	pub start_y: f32,
+	pub strength: f32,
+}
+
+// Stand-in effects used by the photosensitivity safe mode, both off by default
+#[derive(ShaderType, Default, Clone, Copy)]
+pub struct PostProcessingSafety {
+	pub vignette: f32,
+	pub desaturate: f32,
+	pub _wasm_padding_a: f32,
+	pub _wasm_padding_b: f32, // Padded to 16 bytes to make WASM happy
+}
+
+#[derive(ShaderType, Default, Clone, Copy)]
+pub struct PostProcessingMaterialTime {
+	pub time: f32,
+	pub _wasm_padding_a: f32,
+	pub _wasm_padding_b: f32,
+	pub _wasm_padding_c: f32, // Padded to 16 bytes to make WASM happy
+}
+
+#[derive(ShaderType, Default, Clone, Copy)]
+pub struct EffectPassSettings { // 16 bytes, to make WASM happy
+	pub kind: u32,
+	pub amount: f32,
+	pub scale: f32,
+	pub time: f32,
+}
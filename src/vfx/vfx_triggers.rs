@@ -2,20 +2,31 @@ use bevy::prelude::*;
 
 use crate::{
 	game::{physics::{BallCollisionEvent, BallCollisionEventType}, pegs::PegType, food::{DropType, DropCategory}, states::{DayState, GameState}}, 
-	setup::UserData
+	setup::UserData, settings::Settings,
 };
-use super::PostProcessingSettings;
+use super::{PostProcessingSettings, PostProcessingChain, EffectKind};
+
+// Safe mode keeps ripples this many seconds apart
+const SAFE_RIPPLE_INTERVAL: f32 = 8.0;
+// Safe mode replaces the drug hit spike with a much gentler one
+const SAFE_IMPLOSION_STRENGTH: f32 = 2.0;
+// Most of the colour split safe mode allows
+const SAFE_ABERRATION_LIMIT: f32 = 0.3;
 
 pub struct VFXTriggerPlugin;
 
 impl Plugin for VFXTriggerPlugin {
 	fn build(&self, app: &mut App) {
 		app
-		.insert_resource(VFXTriggerSettings {following_velocity: 0.1, unstable_royal_decay: 1.0, ripple_probability: 0.1})
-		//.add_system(ripple_ball_collision.in_set(super::VFXChangeSystemSet).run_if(on_event::<BallCollisionEvent>()))
+		.insert_resource(VFXTriggerSettings {following_velocity: 0.1, unstable_royal_decay: 1.0, ripple_probability: 0.1, ball_ripple_strength: 0.3, ball_ripple_interval: 0.3})
+		.add_system(ripple_ball_collision.in_set(super::VFXChangeSystemSet).run_if(on_event::<BallCollisionEvent>()))
 		.add_system(ripple_mouth_collision.in_set(super::VFXChangeSystemSet).run_if(on_event::<BallCollisionEvent>()))
 		.add_system(drug_implosion.run_if(on_event::<BallCollisionEvent>()))
 		.add_system(follow_stats.in_set(super::VFXChangeSystemSet))
+		.add_system(drug_aberration.in_set(super::VFXChangeSystemSet))
+		.add_system(arcade_scanlines
+			.in_set(super::VFXChangeSystemSet)
+			.run_if(state_changed::<DayState>().or_else(state_changed::<GameState>())))
 		;
 	}
 }
@@ -24,33 +35,45 @@ impl Plugin for VFXTriggerPlugin {
 pub struct VFXTriggerSettings {
 	pub following_velocity: f32,
 	pub unstable_royal_decay: f32,
-	pub ripple_probability: f32
+	pub ripple_probability: f32,
+	// Ball hits ripple weaker than mouths and bells, and at most once every interval seconds
+	pub ball_ripple_strength: f32,
+	pub ball_ripple_interval: f32,
 }
 
-/*fn ripple_ball_collision(
+fn ripple_ball_collision(
 	mut events: EventReader<BallCollisionEvent>,
 	mut post_processing_settings: ResMut<PostProcessingSettings>,
 	windows: Query<&Window>,
 	time: Res<Time>,
 	trigger_settings: Res<VFXTriggerSettings>,
+	player_settings: Res<Settings>,
+	mut last_ripple: Local<Option<f32>>,
 ) {
+	// Safe mode only keeps the ripples that mean something
+	if player_settings.safe_mode || trigger_settings.ripple_probability <= 0.0001 {
+		events.clear();
+		return;
+	}
 	let window = windows.get_single().expect("Single window expected!");
-	// How far has the previous ripple travelled in UV coords
-	let dist = (time.elapsed_seconds() - post_processing_settings.ripple.start_time) * post_processing_settings.ripple.velocity;
-	if trigger_settings.ripple_probability > 0.0001 && dist > 2.0 {
-		for e in events.iter() {
-			if let BallCollisionEventType::Peg {..} = e.collision_type {
-				if rand::random::<f32>() < trigger_settings.ripple_probability {
-					post_processing_settings.ripple.start_time = time.elapsed_seconds();
-					post_processing_settings.ripple.start_x = e.pos.x / window.physical_width() as f32;
-					post_processing_settings.ripple.start_y = 1.0 - e.pos.y / window.physical_height() as f32;
-				}
+	let now = time.elapsed_seconds();
+	for e in events.iter() {
+		if last_ripple.is_some_and(|last| now - last < trigger_settings.ball_ripple_interval) {
+			continue;
+		}
+		if let BallCollisionEventType::Peg {..} = e.collision_type {
+			if rand::random::<f32>() < trigger_settings.ripple_probability {
+				*last_ripple = Some(now);
+				post_processing_settings.ripples.push(
+					now,
+					e.pos.x / window.physical_width() as f32,
+					1.0 - e.pos.y / window.physical_height() as f32,
+					trigger_settings.ball_ripple_strength,
+				);
 			}
 		}
-	} else {
-		events.clear();
 	}
-}*/
+}
 
 fn ripple_mouth_collision(
 	mut events: EventReader<BallCollisionEvent>,
@@ -58,23 +81,25 @@ fn ripple_mouth_collision(
 	windows: Query<&Window>,
 	time: Res<Time>,
 	current_state: Res<State<DayState>>,
+	player_settings: Res<Settings>,
 ) {
 	let window = windows.get_single().expect("Single window expected!");
-	// How far has the previous ripple travelled in UV coords
-	let dist = (time.elapsed_seconds() - post_processing_settings.ripple.start_time) * post_processing_settings.ripple.velocity;
-	if dist > 1.0 {
-		for e in events.iter() {
-			if let BallCollisionEventType::Peg {peg_type: PegType::ItemPeg(drop_type), ball_type, ..} = e.collision_type {
-				if (!drop_type.is_edible() && ball_type == Some(DropType::Mouth)) 
-				|| (current_state.0 == DayState::Night && drop_type == DropType::Bell && ball_type == Some(DropType::Ball)) {
-					post_processing_settings.ripple.start_time = time.elapsed_seconds();
-					post_processing_settings.ripple.start_x = e.pos.x / window.physical_width() as f32;
-					post_processing_settings.ripple.start_y = 1.0 - e.pos.y / window.physical_height() as f32;
+	for e in events.iter() {
+		if let BallCollisionEventType::Peg {peg_type: PegType::ItemPeg(drop_type), ball_type, ..} = e.collision_type {
+			if (!drop_type.is_edible() && ball_type == Some(DropType::Mouth)) 
+			|| (current_state.0 == DayState::Night && drop_type == DropType::Bell && ball_type == Some(DropType::Ball)) {
+				let now = time.elapsed_seconds();
+				if player_settings.safe_mode && now - post_processing_settings.ripples.latest_start() < SAFE_RIPPLE_INTERVAL {
+					continue;
 				}
+				post_processing_settings.ripples.push(
+					now,
+					e.pos.x / window.physical_width() as f32,
+					1.0 - e.pos.y / window.physical_height() as f32,
+					1.0,
+				);
 			}
 		}
-	} else {
-		events.clear();
 	}
 }
 
@@ -82,6 +107,7 @@ fn drug_implosion(
 	_trigger_settings: Res<VFXTriggerSettings>,
 	mut events: EventReader<BallCollisionEvent>,
 	mut user_data: ResMut<UserData>,
+	player_settings: Res<Settings>,
 ) {
 	let mut trigger_implosion = false;
 	for &e in events.iter() {
@@ -97,7 +123,7 @@ fn drug_implosion(
 	}
 	if trigger_implosion {
 		// Makes screen go crazy when you get a hit, quickly diminishing returns tho
-		let effect_strength = 200.0;
+		let effect_strength = if player_settings.safe_mode {SAFE_IMPLOSION_STRENGTH} else {200.0};
 		//println!("Royal: {}", user_data.royal);
 		//println!("Unstable Royal: {}", user_data.royal);
 		user_data.unstable_royal = f32::max(user_data.unstable_royal,
@@ -138,3 +164,33 @@ fn follow_stats(
 	settings.strength.wave_distort = settings.strength.wave_distort.clamp(0.0, 1.6);
 	settings.strength.hue_shift = settings.strength.hue_shift.clamp(0.0, 5.0);
 }
+
+// Colours split apart while a hit is wearing off
+fn drug_aberration(
+	mut chain: ResMut<PostProcessingChain>,
+	user_data: Res<UserData>,
+	player_settings: Res<Settings>,
+) {
+	let limit = if player_settings.safe_mode {SAFE_ABERRATION_LIMIT} else {1.0};
+	let amount = (user_data.unstable_royal * 0.5).min(limit);
+	if let Some(pass) = chain.get(EffectKind::ChromaticAberration) {
+		if pass.amount == amount && pass.enabled == (amount > 0.01) {
+			return;
+		}
+	}
+	if let Some(pass) = chain.get_mut(EffectKind::ChromaticAberration) {
+		pass.amount = amount;
+		pass.enabled = amount > 0.01;
+	}
+}
+
+// The arcade gets an old screen look
+fn arcade_scanlines(
+	mut chain: ResMut<PostProcessingChain>,
+	game_state: Res<State<GameState>>,
+	day_state: Res<State<DayState>>,
+) {
+	if let Some(pass) = chain.get_mut(EffectKind::Crt) {
+		pass.enabled = game_state.0 == GameState::Game && day_state.0 == DayState::Night;
+	}
+}